@@ -0,0 +1,76 @@
+//! Benchmarks N-body steps of a generated asteroid field, with gravity from
+//! the Barnes-Hut quadtree and from summing every body directly.
+//!
+//! ```text
+//! cargo run --release --example asteroid_field [<count>...]
+//! ```
+//!
+//! Prints the time per tick for each body count, against the 16.7 ms a
+//! tick gets at 60 Hz. Bodies don't merge, so every tick is timed with all
+//! of them.
+
+use std::time::Instant;
+
+use gulf::{BarnesHut, Level};
+
+const TICK_RATE: f32 = 60.0;
+/// Ticks timed with the quadtree.
+const TICKS: u32 = 60;
+/// Ticks timed summing directly, which gets slow quickly.
+const DIRECT_TICKS: u32 = 3;
+
+fn level(count: usize) -> Level {
+    let json = format!(
+        r#"{{
+            "ball_start": [-1500.0, 0.0],
+            "bodies": [{{ "pos": [0.0, 0.0], "mass": 3.6e17, "radius": 30.0 }}],
+            "hole": {{ "pos": [1500.0, 0.0] }},
+            "par": 3,
+            "seed": 7,
+            "n_body": true,
+            "asteroids": [{{
+                "count": {},
+                "center": [0.0, 0.0],
+                "inner_radius": 100.0,
+                "outer_radius": 1200.0,
+                "orbit": true
+            }}]
+        }}"#,
+        count
+    );
+    gulf::parse_level(&json).expect("benchmark level is valid")
+}
+
+/// Average milliseconds per tick over `ticks` ticks of `level`.
+fn time_ticks(level: &Level, ticks: u32) -> f64 {
+    let mut world = level.world();
+    let start = Instant::now();
+    for _ in 0..ticks {
+        world.step(1.0 / TICK_RATE);
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / f64::from(ticks)
+}
+
+fn main() {
+    let counts: Vec<usize> = std::env::args()
+        .skip(1)
+        .map(|count| count.parse().expect("counts must be positive integers"))
+        .collect();
+    let counts = if counts.is_empty() { vec![500, 1000, 2000, 5000] } else { counts };
+
+    println!("{:>8} {:>14} {:>14} {:>8}", "bodies", "quadtree ms", "direct ms", "60 Hz");
+    for count in counts {
+        let level = level(count);
+        let bodies = level.all_bodies().len();
+        let tree = time_ticks(&level, TICKS);
+        let direct = time_ticks(
+            &Level {
+                barnes_hut: BarnesHut { threshold: usize::MAX, ..BarnesHut::default() },
+                ..level.clone()
+            },
+            DIRECT_TICKS,
+        );
+        let fits = if tree < 1000.0 / f64::from(TICK_RATE) { "yes" } else { "no" };
+        println!("{:>8} {:>14.2} {:>14.2} {:>8}", bodies, tree, direct, fits);
+    }
+}
@@ -0,0 +1,143 @@
+//! Procedurally generated asteroid fields.
+//!
+//! A level describes each field by where it lies and how big its rocks are,
+//! and the rocks themselves are rolled from the level's `seed` whenever a
+//! world is made from it, so the same level always gets the same field.
+
+use std::f32::consts::PI;
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::body::{BigMass, G};
+
+/// SplitMix64, a tiny generator that gives the same numbers for a seed on
+/// every platform, which replays rely on.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        // the top 24 bits fill an f32 mantissa exactly
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform in `[low, high)`.
+    pub fn range(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * self.next_f32()
+    }
+}
+
+/// A ring of asteroids scattered at random around `center`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AsteroidField {
+    pub count: usize,
+    pub center: na::Point2<f32>,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    #[serde(default = "default_min_radius")]
+    pub min_radius: f32,
+    #[serde(default = "default_max_radius")]
+    pub max_radius: f32,
+    /// Mass per square unit of an asteroid's disc.
+    #[serde(default = "default_density")]
+    pub density: f32,
+    /// Whether the asteroids start on circular orbits around `center`,
+    /// pulled by whatever mass lies inside `inner_radius` and by the rocks
+    /// of the field closer in. Only matters in N-body levels, elsewhere
+    /// asteroids don't move.
+    #[serde(default)]
+    pub orbit: bool,
+}
+
+fn default_min_radius() -> f32 {
+    2.0
+}
+
+fn default_max_radius() -> f32 {
+    6.0
+}
+
+fn default_density() -> f32 {
+    1e13
+}
+
+impl AsteroidField {
+    /// Tries per asteroid at finding a spot away from everything in `keep_clear`.
+    const MAX_TRIES: usize = 16;
+
+    /// Rolls the asteroids of this field from `rng`. Asteroids stay out of
+    /// the circles in `keep_clear`, given as center and radius, and of each
+    /// other, and orbit `central_mass` if the field asks for it.
+    pub fn generate(
+        &self,
+        rng: &mut Rng,
+        central_mass: f32,
+        keep_clear: &[(na::Point2<f32>, f32)],
+    ) -> Vec<BigMass> {
+        let mut keep_clear = keep_clear.to_vec();
+        let mut asteroids = Vec::with_capacity(self.count);
+        for _ in 0..self.count {
+            for _ in 0..Self::MAX_TRIES {
+                let radius = rng.range(self.min_radius, self.max_radius);
+                // uniform over the ring's area rather than its radius
+                let distance = rng.range(self.inner_radius.powi(2), self.outer_radius.powi(2)).sqrt();
+                let angle = rng.range(0.0, 2.0 * PI);
+                let pos = self.center + na::Vector2::new(angle.cos(), angle.sin()) * distance;
+                let blocked = keep_clear.iter()
+                    .any(|&(center, clear)| na::distance(&center, &pos) < clear + radius);
+                if blocked {
+                    continue;
+                }
+
+                keep_clear.push((pos, radius));
+                asteroids.push(BigMass::new(pos, self.density * PI * radius.powi(2), radius));
+                break;
+            }
+        }
+        if self.orbit {
+            self.start_orbits(&mut asteroids, central_mass);
+        }
+        asteroids
+    }
+
+    /// Puts `asteroids` on circular orbits around `center`. The ring can
+    /// easily outweigh what it circles, so each asteroid is pulled by
+    /// `central_mass` plus the asteroids closer in, as if those sat at the
+    /// center.
+    fn start_orbits(&self, asteroids: &mut [BigMass], central_mass: f32) {
+        let mut order: Vec<usize> = (0..asteroids.len()).collect();
+        order.sort_by(|&a, &b| {
+            let a = na::distance(&self.center, &asteroids[a].pos);
+            let b = na::distance(&self.center, &asteroids[b].pos);
+            a.total_cmp(&b)
+        });
+        let mut enclosed = central_mass;
+        for i in order {
+            let asteroid = &mut asteroids[i];
+            let offset = asteroid.pos - self.center;
+            let distance = offset.magnitude();
+            if distance > 0.0 {
+                // v = sqrt(GM / r), clockwise on screen like scripted orbits
+                let speed = (G * enclosed / distance).sqrt();
+                asteroid.vel = na::Vector2::new(-offset.y, offset.x) / distance * speed;
+            }
+            enclosed += asteroid.mass;
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Barnes-Hut approximation of gravity for levels with many bodies.
+//!
+//! Bodies are sorted into a quadtree where every node knows the total mass
+//! and center of mass of the bodies below it. A node that looks small from
+//! where the pull is measured, its width over its distance below `theta`,
+//! pulls like a single point mass instead of body by body. That takes a
+//! field evaluation from O(bodies) to O(log bodies), and a whole N-body step
+//! from O(bodies^2) to O(bodies log bodies).
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::body::{BigMass, G};
+
+/// When and how coarsely to approximate gravity.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BarnesHut {
+    /// Largest width over distance a node can have and still pull as one
+    /// point mass. 0 is exact, larger is faster and rougher.
+    pub theta: f32,
+    /// Body count above which the quadtree is used, below it summing every
+    /// body directly is faster.
+    pub threshold: usize,
+}
+
+impl Default for BarnesHut {
+    fn default() -> BarnesHut {
+        BarnesHut {
+            theta: 0.5,
+            threshold: 64,
+        }
+    }
+}
+
+impl BarnesHut {
+    pub fn is_default(&self) -> bool {
+        *self == BarnesHut::default()
+    }
+
+    /// A quadtree over `bodies` if there are enough of them to need one.
+    pub fn tree(&self, bodies: &[BigMass]) -> Option<QuadTree> {
+        if bodies.len() > self.threshold {
+            Some(QuadTree::new(bodies))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Contents {
+    /// Bodies by index, normally just one, more only if they are too close
+    /// together to split.
+    Leaf(Vec<usize>),
+    /// Index of the first of four children, in the order of
+    /// [`QuadTree::quadrant`].
+    Split(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    center: na::Point2<f32>,
+    half_size: f32,
+    mass: f32,
+    center_of_mass: na::Point2<f32>,
+    contents: Contents,
+}
+
+impl Node {
+    fn new(center: na::Point2<f32>, half_size: f32) -> Node {
+        Node {
+            center,
+            half_size,
+            mass: 0.0,
+            center_of_mass: center,
+            contents: Contents::Leaf(vec![]),
+        }
+    }
+
+    fn contains(&self, pos: na::Point2<f32>) -> bool {
+        (pos.x - self.center.x).abs() <= self.half_size
+            && (pos.y - self.center.y).abs() <= self.half_size
+    }
+}
+
+/// Quadtree over the positions and masses of a set of bodies. It has to be
+/// rebuilt whenever they move.
+#[derive(Debug, Clone)]
+pub struct QuadTree {
+    nodes: Vec<Node>,
+}
+
+impl QuadTree {
+    /// Splits stop this deep, leaving bodies that are still together in one
+    /// leaf.
+    const MAX_DEPTH: usize = 24;
+
+    pub fn new(bodies: &[BigMass]) -> QuadTree {
+        let (min, max) = bodies.iter().fold(
+            (na::Point2::new(f32::MAX, f32::MAX), na::Point2::new(f32::MIN, f32::MIN)),
+            |(min, max), body| (
+                na::Point2::new(min.x.min(body.pos.x), min.y.min(body.pos.y)),
+                na::Point2::new(max.x.max(body.pos.x), max.y.max(body.pos.y)),
+            ),
+        );
+        let root = if bodies.is_empty() {
+            Node::new(na::Point2::origin(), 1.0)
+        } else {
+            let half_size = ((max.x - min.x).max(max.y - min.y) / 2.0).max(1.0);
+            Node::new(na::center(&min, &max), half_size)
+        };
+
+        let mut tree = QuadTree { nodes: vec![root] };
+        for i in 0..bodies.len() {
+            tree.insert(bodies, i);
+        }
+        tree.summarize(0, bodies);
+        tree
+    }
+
+    /// Which child of `node` `pos` falls in: 0 and 1 above its center, 0 and
+    /// 2 left of it.
+    fn quadrant(node: &Node, pos: na::Point2<f32>) -> usize {
+        let right = (pos.x > node.center.x) as usize;
+        let below = (pos.y > node.center.y) as usize;
+        right + 2 * below
+    }
+
+    fn insert(&mut self, bodies: &[BigMass], body: usize) {
+        let pos = bodies[body].pos;
+        let mut index = 0;
+        let mut depth = 0;
+        loop {
+            match &mut self.nodes[index].contents {
+                Contents::Split(first) => {
+                    index = *first + Self::quadrant(&self.nodes[index], pos);
+                    depth += 1;
+                },
+                Contents::Leaf(list) if list.is_empty() || depth >= Self::MAX_DEPTH => {
+                    list.push(body);
+                    return;
+                },
+                Contents::Leaf(list) => {
+                    let list = std::mem::take(list);
+                    let first = self.split(index);
+                    for other in list {
+                        let child = first + Self::quadrant(&self.nodes[index], bodies[other].pos);
+                        if let Contents::Leaf(child_list) = &mut self.nodes[child].contents {
+                            child_list.push(other);
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Gives `index` four empty children and returns the first one's index.
+    fn split(&mut self, index: usize) -> usize {
+        let first = self.nodes.len();
+        let node = &self.nodes[index];
+        let half_size = node.half_size / 2.0;
+        let center = node.center;
+        for &(dx, dy) in &[(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+            let child_center = center + na::Vector2::new(dx, dy) * half_size;
+            self.nodes.push(Node::new(child_center, half_size));
+        }
+        self.nodes[index].contents = Contents::Split(first);
+        first
+    }
+
+    /// Fills in the mass and center of mass of `index` and everything below.
+    fn summarize(&mut self, index: usize, bodies: &[BigMass]) {
+        let (mass, moment) = match &self.nodes[index].contents {
+            Contents::Leaf(list) => list.iter().fold((0.0, na::Vector2::zeros()), |(mass, moment), &i| {
+                (mass + bodies[i].mass, moment + bodies[i].pos.coords * bodies[i].mass)
+            }),
+            &Contents::Split(first) => {
+                let mut mass = 0.0;
+                let mut moment = na::Vector2::zeros();
+                for child in first..first + 4 {
+                    self.summarize(child, bodies);
+                    let child = &self.nodes[child];
+                    mass += child.mass;
+                    moment += child.center_of_mass.coords * child.mass;
+                }
+                (mass, moment)
+            },
+        };
+        let node = &mut self.nodes[index];
+        node.mass = mass;
+        if mass > 0.0 {
+            node.center_of_mass = na::Point2::from(moment / mass);
+        }
+    }
+
+    /// Acceleration at `pos`, with bodies in opened leaves pulling through
+    /// `leaf` by index and far away nodes as point masses.
+    pub fn acceleration_at<F>(&self, pos: na::Point2<f32>, theta: f32, leaf: F) -> na::Vector2<f32>
+    where
+        F: Fn(usize) -> na::Vector2<f32>,
+    {
+        let mut acc = na::Vector2::zeros();
+        let mut stack = Vec::with_capacity(4 * Self::MAX_DEPTH);
+        stack.push(0);
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if node.mass <= 0.0 {
+                continue;
+            }
+            match &node.contents {
+                Contents::Leaf(list) => {
+                    for &i in list {
+                        acc += leaf(i);
+                    }
+                },
+                &Contents::Split(first) => {
+                    let offset = node.center_of_mass - pos;
+                    let distance = offset.magnitude();
+                    if !node.contains(pos) && 2.0 * node.half_size < theta * distance {
+                        acc += offset * (G * node.mass / distance.powi(3));
+                    } else {
+                        stack.extend(first..first + 4);
+                    }
+                },
+            }
+        }
+        acc
+    }
+}
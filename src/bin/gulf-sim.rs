@@ -0,0 +1,331 @@
+//! Runs shots on a level without opening a window and prints what happened
+//! as JSON.
+//!
+//! ```text
+//! gulf-sim [--tick-rate <hz>] [--max-ticks <n>] [--integrator <method>] <level> [<shots>]
+//! gulf-sim verify [--tick-rate <hz>] [--integrator <method>] <level or directory>...
+//! gulf-sim replay <level> <replay>
+//! ```
+//!
+//! Shots are read from the `<shots>` file, or stdin when it's missing or
+//! `-`, one per line as `<angle> <power>`, with the angle in degrees from the
+//! x axis towards positive y. Blank lines and lines starting with `#` are
+//! skipped. Each shot is taken once the ball has come to rest from the last,
+//! and the run ends early, without taking the rest, if the ball is sunk or
+//! still moving after `--max-ticks` steps.
+//!
+//! `--integrator` overrides how the level moves the ball, one of `euler`,
+//! `semi_implicit_euler`, `velocity_verlet` or `rk4`.
+//!
+//! `verify` checks every given level, or every `.json` level in a given
+//! directory, and reports the fewest strokes the solver needed and anything
+//! wrong with it. It exits with an error if any level has issues.
+//!
+//! `replay` plays a replay saved from the game back on its level and exits
+//! with an error unless it ends exactly where it was recorded ending.
+
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process;
+
+use nalgebra as na;
+use serde::Serialize;
+
+use gulf::verify;
+use gulf::{Collision, Fault, Level, Method, Replay, Shot, SolverConfig};
+
+const USAGE: &str = "usage: gulf-sim [--tick-rate <hz>] [--max-ticks <n>] [--integrator <method>] <level> [<shots>]\n       \
+    gulf-sim verify [--tick-rate <hz>] [--integrator <method>] <level or directory>...\n       \
+    gulf-sim replay <level> <replay>";
+
+struct Options {
+    tick_rate: u32,
+    /// Steps to wait for the ball to come to rest after each shot.
+    max_ticks: u64,
+    integrator: Option<Method>,
+    level_path: String,
+    shots_path: Option<String>,
+}
+
+impl Options {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+        let mut tick_rate = 60;
+        let mut max_ticks = 60 * 60;
+        let mut integrator = None;
+        let mut paths = vec![];
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--tick-rate" => {
+                    tick_rate = args.next()
+                        .and_then(|rate| rate.parse().ok())
+                        .filter(|&rate| rate > 0)
+                        .ok_or("--tick-rate expects a positive integer")?;
+                },
+                "--max-ticks" => {
+                    max_ticks = args.next()
+                        .and_then(|ticks| ticks.parse().ok())
+                        .ok_or("--max-ticks expects a non-negative integer")?;
+                },
+                "--integrator" => integrator = Some(parse_integrator(args.next())?),
+                flag if flag.starts_with("--") => return Err(unknown_flag(flag)),
+                _ => paths.push(arg),
+            }
+        }
+
+        let mut paths = paths.into_iter();
+        let level_path = paths.next().ok_or(USAGE)?;
+        let shots_path = paths.next().filter(|path| path != "-");
+        if paths.next().is_some() {
+            return Err(USAGE.to_string());
+        }
+        Ok(Options { tick_rate, max_ticks, integrator, level_path, shots_path })
+    }
+}
+
+fn unknown_flag(flag: &str) -> String {
+    format!("unknown option {}\n{}", flag, USAGE)
+}
+
+fn parse_integrator(arg: Option<String>) -> Result<Method, String> {
+    arg.ok_or_else(|| "--integrator expects euler, semi_implicit_euler, velocity_verlet or rk4".to_string())
+        .and_then(|integrator| integrator.parse())
+}
+
+#[derive(Serialize)]
+struct ShotReport {
+    angle: f32,
+    power: f32,
+    /// Tick the shot was taken on.
+    tick: u64,
+    /// Ball position after every step until it came to rest.
+    trajectory: Vec<na::Point2<f32>>,
+    collisions: Vec<Collision>,
+    /// Whether the ball was still moving when `max_ticks` ran out, which
+    /// ends the run.
+    timed_out: bool,
+    /// The level rule the shot broke, sending the ball back.
+    fault: Option<Fault>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    level_hash: u64,
+    tick_rate: u32,
+    shots: Vec<ShotReport>,
+    /// Shots plus penalties.
+    strokes: u32,
+    penalties: u32,
+    holed: bool,
+    final_pos: na::Point2<f32>,
+}
+
+fn parse_shots(input: &str) -> Result<Vec<Shot>, String> {
+    input.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_no, line)| {
+            let fields: Vec<f32> = line.split_whitespace()
+                .map(|field| field.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|err| format!("line {}: {}", line_no, err))?;
+            match fields.as_slice() {
+                &[angle, power] => Ok(Shot::from_angle(angle.to_radians(), power)),
+                _ => Err(format!("line {}: expected '<angle> <power>'", line_no)),
+            }
+        })
+        .collect()
+}
+
+fn simulate(level: &Level, shots: &[Shot], options: &Options) -> Report {
+    let dt = 1.0 / options.tick_rate as f32;
+    let mut world = level.world();
+    let mut reports = vec![];
+    for shot in shots {
+        if world.is_sunk() || world.ball.is_moving() {
+            break;
+        }
+
+        let tick = world.tick();
+        world.shoot(shot.force());
+        let mut trajectory = vec![];
+        let mut collisions = vec![];
+        let mut fault = None;
+        world.settle_with(dt, options.max_ticks, |world| {
+            trajectory.push(world.ball.pos);
+            collisions.extend_from_slice(&world.collisions);
+            fault = fault.or(world.fault);
+        });
+        reports.push(ShotReport {
+            angle: shot.angle().to_degrees(),
+            power: shot.power,
+            tick,
+            trajectory,
+            collisions,
+            timed_out: world.ball.is_moving() && !world.is_sunk(),
+            fault,
+        });
+    }
+
+    Report {
+        level_hash: gulf::level_hash(level),
+        tick_rate: options.tick_rate,
+        strokes: reports.len() as u32 + world.penalties(),
+        penalties: world.penalties(),
+        shots: reports,
+        holed: world.is_sunk(),
+        final_pos: world.ball.pos,
+    }
+}
+
+#[derive(Serialize)]
+struct LevelReport {
+    path: PathBuf,
+    #[serde(flatten)]
+    report: verify::Report,
+}
+
+/// The levels named by `paths`, with directories standing for all the
+/// `.json` files in them in name order.
+fn level_paths(paths: Vec<String>) -> io::Result<Vec<PathBuf>> {
+    let mut levels = vec![];
+    for path in paths {
+        let path = PathBuf::from(path);
+        if path.is_dir() {
+            let mut entries = fs::read_dir(&path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()?;
+            entries.retain(|entry| entry.extension().is_some_and(|ext| ext == "json"));
+            entries.sort();
+            levels.extend(entries);
+        } else {
+            levels.push(path);
+        }
+    }
+    Ok(levels)
+}
+
+fn verify_levels<I: Iterator<Item = String>>(mut args: I) -> Result<(), Box<dyn Error>> {
+    let mut config = SolverConfig::default();
+    let mut integrator = None;
+    let mut paths = vec![];
+    while let Some(arg) = args.next() {
+        if arg == "--tick-rate" {
+            config.tick_rate = args.next()
+                .and_then(|rate| rate.parse().ok())
+                .filter(|&rate| rate > 0)
+                .ok_or("--tick-rate expects a positive integer")?;
+        } else if arg == "--integrator" {
+            integrator = Some(parse_integrator(args.next())?);
+        } else if arg.starts_with("--") {
+            return Err(unknown_flag(&arg).into());
+        } else {
+            paths.push(arg);
+        }
+    }
+    if paths.is_empty() {
+        return Err(USAGE.into());
+    }
+
+    let mut reports = vec![];
+    for path in level_paths(paths)? {
+        let mut level = gulf::load_level(&path)
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+        if let Some(integrator) = integrator {
+            level.integrator = integrator;
+        }
+        let report = verify::verify_level(&level, &config);
+        reports.push(LevelReport { path, report });
+    }
+    println!("{}", serde_json::to_string_pretty(&reports)?);
+
+    let failed: Vec<_> = reports.iter()
+        .filter(|level| !level.report.is_ok())
+        .map(|level| level.path.display().to_string())
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("levels with issues: {}", failed.join(", ")).into())
+    }
+}
+
+#[derive(Serialize)]
+struct ReplayReport {
+    level_hash: u64,
+    tick_rate: u32,
+    shots: usize,
+    final_tick: u64,
+    final_pos: na::Point2<f32>,
+    holed: bool,
+    penalties: u32,
+}
+
+fn verify_replay<I: Iterator<Item = String>>(args: I) -> Result<(), Box<dyn Error>> {
+    let paths: Vec<_> = args.collect();
+    if let Some(flag) = paths.iter().find(|path| path.starts_with("--")) {
+        return Err(unknown_flag(flag).into());
+    }
+    let (level_path, replay_path) = match paths.as_slice() {
+        [level, replay] => (level, replay),
+        _ => return Err(USAGE.into()),
+    };
+    let level = gulf::load_level(level_path)?;
+    let replay = Replay::load(replay_path)?;
+    let world = replay.verify(&level)?;
+
+    let report = ReplayReport {
+        level_hash: replay.level_hash,
+        tick_rate: replay.tick_rate,
+        shots: replay.shots.len(),
+        final_tick: world.tick(),
+        final_pos: world.ball.pos,
+        holed: world.is_sunk(),
+        penalties: world.penalties(),
+    };
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1).peekable();
+    match args.peek().map(String::as_str) {
+        Some("verify") => {
+            args.next();
+            return verify_levels(args);
+        },
+        Some("replay") => {
+            args.next();
+            return verify_replay(args);
+        },
+        _ => (),
+    }
+
+    let options = Options::parse(args)?;
+    let mut level = gulf::load_level(&options.level_path)?;
+    if let Some(integrator) = options.integrator {
+        level.integrator = integrator;
+    }
+    let input = match &options.shots_path {
+        Some(path) => fs::read_to_string(path)?,
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            input
+        },
+    };
+    let shots = parse_shots(&input)?;
+
+    let report = simulate(&level, &shots, &options);
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("gulf-sim: {}", err);
+        process::exit(1);
+    }
+}
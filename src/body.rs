@@ -0,0 +1,104 @@
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::drag::Atmosphere;
+use crate::motion::Motion;
+
+/// Newtonian constant of gravitation.
+pub const G: f32 = 6.674e-11;
+
+/// How a surface responds to the ball hitting it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    /// Fraction of the normal speed kept after a bounce, 0 is fully inelastic.
+    pub restitution: f32,
+    /// Coulomb friction coefficient slowing the ball along the surface.
+    pub friction: f32,
+}
+
+impl Default for Material {
+    fn default() -> Material {
+        Material {
+            restitution: 0.5,
+            friction: 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BigMass {
+    pub pos: na::Point2<f32>,
+    pub mass: f32,
+    pub radius: f32,
+    #[serde(default)]
+    pub material: Material,
+    /// Only written for moving bodies, so levels without any keep the same
+    /// [`level_hash`](crate::replay::level_hash) and their old replays.
+    #[serde(default, skip_serializing_if = "Motion::is_static")]
+    pub motion: Motion,
+    /// How fast the body is moving, kept up to date by the world it is in.
+    /// In the level file this is the starting velocity of a free body in an
+    /// N-body level, left out when zero.
+    #[serde(default = "na::Vector2::zeros", skip_serializing_if = "is_zero")]
+    pub vel: na::Vector2<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atmosphere: Option<Atmosphere>,
+}
+
+fn is_zero(vel: &na::Vector2<f32>) -> bool {
+    *vel == na::Vector2::zeros()
+}
+
+impl BigMass {
+    pub fn new(pos: na::Point2<f32>, mass: f32, radius: f32) -> BigMass {
+        BigMass {
+            pos,
+            mass,
+            radius,
+            material: Material::default(),
+            motion: Motion::Static,
+            vel: na::Vector2::zeros(),
+            atmosphere: None,
+        }
+    }
+
+    pub fn with_material(mut self, material: Material) -> BigMass {
+        self.material = material;
+        self
+    }
+
+    pub fn with_motion(mut self, motion: Motion) -> BigMass {
+        self.motion = motion;
+        self
+    }
+
+    /// Magnitude of the pull on a mass `other_mass` at `distance` from the
+    /// center. Inside the surface the distance is clamped to the radius so the
+    /// force stays finite when the ball grazes or overlaps the body.
+    pub fn gravity(&self, distance: f32, other_mass: f32) -> f32 {
+        let distance = distance.max(self.radius);
+        G * self.mass * other_mass / distance.powi(2)
+    }
+
+    /// Gravitational potential per unit mass at `pos`, clamped to its value
+    /// at the surface like [`BigMass::gravity`].
+    pub fn potential_at(&self, pos: na::Point2<f32>) -> f32 {
+        let distance = na::distance(&self.pos, &pos).max(self.radius);
+        -G * self.mass / distance
+    }
+
+    /// Acceleration this body gives a mass `other_mass` located at `pos`.
+    pub fn acceleration_at(&self, pos: na::Point2<f32>, other_mass: f32) -> na::Vector2<f32> {
+        const EPSILON: f32 = 1e-2;
+
+        let body_dir = self.pos - pos;
+        match body_dir.try_normalize(EPSILON) {
+            Some(dir) => {
+                // F = GMm / r^2, a = F / m
+                let force = self.gravity(body_dir.magnitude(), other_mass);
+                dir * force / other_mass
+            },
+            None => na::Vector2::zeros(),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use nalgebra as na;
+
+/// Maps between world coordinates and the pixels of a viewport.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    /// World point shown at the center of the viewport.
+    pub center: na::Point2<f32>,
+    /// Screen pixels per world unit.
+    pub zoom: f32,
+}
+
+impl Camera {
+    pub const MIN_ZOOM: f32 = 0.1;
+    pub const MAX_ZOOM: f32 = 10.0;
+    /// How quickly [`Camera::follow`] closes the gap to its target, per second.
+    pub const FOLLOW_RATE: f32 = 4.0;
+
+    pub fn new(center: na::Point2<f32>) -> Camera {
+        Camera { center, zoom: 1.0 }
+    }
+
+    /// World position of the top-left corner of a `viewport` sized view,
+    /// and how much of the world it covers.
+    pub fn view(&self, viewport: (f32, f32)) -> (na::Point2<f32>, na::Vector2<f32>) {
+        let size = na::Vector2::new(viewport.0, viewport.1) / self.zoom;
+        (self.center - size / 2.0, size)
+    }
+
+    pub fn screen_to_world(&self, screen: na::Point2<f32>, viewport: (f32, f32)) -> na::Point2<f32> {
+        let (origin, _) = self.view(viewport);
+        origin + screen.coords / self.zoom
+    }
+
+    pub fn world_to_screen(&self, world: na::Point2<f32>, viewport: (f32, f32)) -> na::Point2<f32> {
+        let (origin, _) = self.view(viewport);
+        na::Point2::from((world - origin) * self.zoom)
+    }
+
+    /// Scales the zoom by `factor`, keeping the world point under `screen`
+    /// in place.
+    pub fn zoom_at(&mut self, factor: f32, screen: na::Point2<f32>, viewport: (f32, f32)) {
+        let anchor = self.screen_to_world(screen, viewport);
+        self.zoom = (self.zoom * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        self.center += anchor - self.screen_to_world(screen, viewport);
+    }
+
+    /// Moves the view so the world follows a drag of `screen_delta` pixels.
+    pub fn pan(&mut self, screen_delta: na::Vector2<f32>) {
+        self.center -= screen_delta / self.zoom;
+    }
+
+    /// Eases the view towards `target` over `dt` seconds.
+    pub fn follow(&mut self, target: na::Point2<f32>, dt: f32) {
+        let t = 1.0 - (-Self::FOLLOW_RATE * dt).exp();
+        self.center += (target - self.center) * t;
+    }
+}
@@ -0,0 +1,225 @@
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::body::{BigMass, Material};
+use crate::wall::Wall;
+use crate::world::Ball;
+
+/// Normal speed below which a contact doesn't bounce, so the ball settles on
+/// a surface instead of jittering on it.
+pub const BOUNCE_THRESHOLD: f32 = 5.0;
+
+/// What the ball ran into, by index into the world's bodies, walls or
+/// obstacles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Collider {
+    Body(usize),
+    Wall(usize),
+    /// The `edge`th edge of an obstacle, the one from its `edge`th corner
+    /// to the next.
+    Obstacle { obstacle: usize, edge: usize },
+}
+
+/// The ball hitting something during a step.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Collision {
+    /// [`World::tick`](crate::World::tick) right after the step the
+    /// collision happened in.
+    pub tick: u64,
+    pub collider: Collider,
+    /// Ball position at the moment of contact.
+    pub pos: na::Point2<f32>,
+    /// Outward surface normal at the contact.
+    pub normal: na::Vector2<f32>,
+    /// Speed the ball was approaching the surface with.
+    pub speed: f32,
+}
+
+impl Collision {
+    /// The ball pushed out of `collider` to `pos` on `tick` by a [`Contact`]
+    /// the sweep didn't catch.
+    pub fn from_contact(tick: u64, collider: Collider, pos: na::Point2<f32>, contact: Contact) -> Collision {
+        Collision {
+            tick,
+            collider,
+            pos,
+            normal: contact.normal,
+            speed: contact.speed,
+        }
+    }
+}
+
+/// First contact of a moving circle along its path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Impact {
+    /// Fraction of the motion travelled before touching, in `[0, 1]`.
+    pub toi: f32,
+    /// Outward surface normal at the contact.
+    pub normal: na::Vector2<f32>,
+}
+
+/// The ball found overlapping a surface and pushed back out of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// Outward surface normal at the contact.
+    pub normal: na::Vector2<f32>,
+    /// Speed the ball was approaching the surface with, 0 if it wasn't.
+    pub speed: f32,
+}
+
+/// Sweeps a circle of `radius` from `start` along `motion` against a static
+/// circle and returns the earliest contact, if any.
+///
+/// A circle that already overlaps only reports an impact while it is still
+/// moving inwards, so one resting against the surface can slide along it.
+pub fn sweep_circle_circle(
+    start: na::Point2<f32>,
+    motion: na::Vector2<f32>,
+    radius: f32,
+    center: na::Point2<f32>,
+    other_radius: f32,
+) -> Option<Impact> {
+    const EPSILON: f32 = 1e-6;
+
+    let min_dist = radius + other_radius;
+    let offset = start - center;
+    let approach = offset.dot(&motion);
+    let c = offset.magnitude_squared() - min_dist * min_dist;
+    if c <= 0.0 {
+        return if approach < 0.0 {
+            offset.try_normalize(EPSILON).map(|normal| Impact { toi: 0.0, normal })
+        } else {
+            None
+        };
+    }
+
+    // solve |offset + motion * t| = min_dist for the smaller root
+    let a = motion.magnitude_squared();
+    if a < EPSILON || approach >= 0.0 {
+        return None;
+    }
+    let discriminant = approach * approach - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let toi = (-approach - discriminant.sqrt()) / a;
+    if toi > 1.0 {
+        return None;
+    }
+    let toi = toi.max(0.0);
+    let normal = (offset + motion * toi) / min_dist;
+    Some(Impact { toi, normal })
+}
+
+/// Sweeps a circle of `radius` from `start` along `motion` against a wall and
+/// returns the earliest contact with either its face or one of its ends.
+pub fn sweep_circle_wall(
+    start: na::Point2<f32>,
+    motion: na::Vector2<f32>,
+    radius: f32,
+    wall: &Wall,
+) -> Option<Impact> {
+    const EPSILON: f32 = 1e-6;
+
+    let along = wall.b - wall.a;
+    let face = na::Vector2::new(-along.y, along.x).try_normalize(EPSILON).and_then(|normal| {
+        // face the normal towards the side the circle starts on
+        let dist = (start - wall.a).dot(&normal);
+        let (normal, dist) = if dist < 0.0 { (-normal, -dist) } else { (normal, dist) };
+        let approach = motion.dot(&normal);
+        if approach >= 0.0 {
+            return None;
+        }
+        let toi = ((dist - radius) / -approach).max(0.0);
+        if toi > 1.0 {
+            return None;
+        }
+        let contact = start + motion * toi;
+        let s = (contact - wall.a).dot(&along) / along.magnitude_squared();
+        if (0.0..=1.0).contains(&s) {
+            Some(Impact { toi, normal })
+        } else {
+            None
+        }
+    });
+    let ends = [wall.a, wall.b];
+    let ends = ends.iter()
+        .filter_map(|&end| sweep_circle_circle(start, motion, radius, end, 0.0));
+
+    face.into_iter()
+        .chain(ends)
+        .min_by(|a, b| a.toi.total_cmp(&b.toi))
+}
+
+/// Pushes the ball out of `body` if they overlap and responds to the impact.
+/// Returns the contact if there was one.
+pub fn collide_ball_body(ball: &mut Ball, body: &BigMass) -> Option<Contact> {
+    const EPSILON: f32 = 1e-4;
+
+    let min_dist = Ball::RADIUS + body.radius;
+    let offset = ball.pos - body.pos;
+    let dist = offset.magnitude();
+    if dist >= min_dist {
+        return None;
+    }
+
+    // a ball dead on the center has no meaningful normal, push it out upwards
+    let normal = offset.try_normalize(EPSILON).unwrap_or_else(|| -na::Vector2::y());
+    let vel = ball.vel - body.vel;
+    ball.pos = body.pos + normal * min_dist;
+    ball.vel = body.vel + bounce(vel, normal, &body.material);
+    Some(Contact { normal, speed: (-vel.dot(&normal)).max(0.0) })
+}
+
+/// Velocity after hitting a surface with outward `normal`.
+pub fn bounce(vel: na::Vector2<f32>, normal: na::Vector2<f32>, material: &Material) -> na::Vector2<f32> {
+    let normal_speed = vel.dot(&normal);
+    if normal_speed >= 0.0 {
+        // already separating
+        return vel;
+    }
+
+    let tangent_vel = vel - normal * normal_speed;
+    let restitution = if -normal_speed < BOUNCE_THRESHOLD {
+        0.0
+    } else {
+        material.restitution
+    };
+    // the normal impulse bounds how much friction can slow the ball down
+    let normal_impulse = -normal_speed * (1.0 + restitution);
+    let tangent_speed = tangent_vel.magnitude();
+    let tangent_vel = if tangent_speed > 0.0 {
+        let slowed = (tangent_speed - material.friction * normal_impulse).max(0.0);
+        tangent_vel * (slowed / tangent_speed)
+    } else {
+        tangent_vel
+    };
+
+    tangent_vel - normal * normal_speed * restitution
+}
+
+/// Pushes the ball out of `wall` if they overlap and responds to the impact.
+/// Returns the contact if there was one.
+pub fn collide_ball_wall(ball: &mut Ball, wall: &Wall) -> Option<Contact> {
+    const EPSILON: f32 = 1e-4;
+
+    let closest = wall.closest_point(ball.pos);
+    let offset = ball.pos - closest;
+    if offset.magnitude() >= Ball::RADIUS {
+        return None;
+    }
+
+    let normal = match offset.try_normalize(EPSILON) {
+        Some(normal) => normal,
+        // dead on the wall, push it out along its normal
+        None => {
+            let along = wall.b - wall.a;
+            na::Vector2::new(-along.y, along.x).try_normalize(EPSILON).unwrap_or_else(|| -na::Vector2::y())
+        },
+    };
+    let speed = (-ball.vel.dot(&normal)).max(0.0);
+    ball.pos = closest + normal * Ball::RADIUS;
+    ball.vel = bounce(ball.vel, normal, &wall.material);
+    Some(Contact { normal, speed })
+}
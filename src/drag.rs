@@ -0,0 +1,75 @@
+//! Drag slowing the ball down as it flies.
+//!
+//! A level has one [`Drag`] acting everywhere, and bodies can add an
+//! [`Atmosphere`] of quadratic drag around themselves. Drag is applied after
+//! the integrator has moved the ball, using the exact solution over the step
+//! so even very strong drag only ever slows the ball and never reverses it.
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+/// Drag acting everywhere in a level.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Drag {
+    /// Nothing slows the ball but what it runs into, for space levels.
+    None,
+    /// Deceleration of `coefficient` times the speed, per second.
+    Linear { coefficient: f32 },
+    /// Deceleration of `coefficient` times the speed squared, per unit.
+    Quadratic { coefficient: f32 },
+}
+
+impl Drag {
+    /// Gentle linear drag, as from a thin gas filling the course. The ball
+    /// keeps about a fifth of its speed after a second, so a shot glides for
+    /// a few seconds and gravity has time to bend its path.
+    pub const DEFAULT_COEFFICIENT: f32 = 1.5;
+
+    pub fn is_default(&self) -> bool {
+        *self == Drag::default()
+    }
+
+    /// Linear and quadratic coefficients of this drag.
+    pub fn coefficients(&self) -> (f32, f32) {
+        match *self {
+            Drag::None => (0.0, 0.0),
+            Drag::Linear { coefficient } => (coefficient, 0.0),
+            Drag::Quadratic { coefficient } => (0.0, coefficient),
+        }
+    }
+}
+
+impl Default for Drag {
+    fn default() -> Drag {
+        Drag::Linear { coefficient: Drag::DEFAULT_COEFFICIENT }
+    }
+}
+
+/// Air around a body giving quadratic drag, thickest at the surface and
+/// thinning out to nothing `height` above it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Atmosphere {
+    pub height: f32,
+    /// Quadratic drag coefficient at the surface.
+    pub coefficient: f32,
+}
+
+impl Atmosphere {
+    /// Quadratic drag coefficient at `distance` from the center of a body
+    /// of `radius`.
+    pub fn coefficient_at(&self, radius: f32, distance: f32) -> f32 {
+        if self.height <= 0.0 {
+            return 0.0;
+        }
+        let altitude = (distance - radius).max(0.0);
+        self.coefficient * (1.0 - altitude / self.height).max(0.0)
+    }
+}
+
+/// `vel` after `dt` seconds of `linear` and `quadratic` drag.
+pub fn apply(vel: na::Vector2<f32>, linear: f32, quadratic: f32, dt: f32) -> na::Vector2<f32> {
+    // dv/dt = -k v gives v e^(-kt), dv/dt = -k |v| v gives v / (1 + k |v| t)
+    let vel = vel * (-linear * dt).exp();
+    vel / (1.0 + quadratic * vel.magnitude() * dt)
+}
@@ -0,0 +1,253 @@
+use std::path::Path;
+
+use nalgebra as na;
+
+use crate::body::BigMass;
+use crate::level::{self, Level, LevelError};
+use crate::motion;
+use crate::world::Ball;
+
+/// Something in a level the editor can grab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Body(usize),
+    BallStart,
+    Hole,
+}
+
+#[derive(Debug, Clone)]
+struct Drag {
+    /// Offset from the grabbed point to the selection.
+    offset: na::Vector2<f32>,
+    /// Level before the drag, pushed to the undo stack once it actually moves.
+    before: Option<Level>,
+}
+
+/// Edits a [`Level`] in place, keeping snapshots to undo and redo changes.
+#[derive(Debug, Clone)]
+pub struct Editor {
+    level: Level,
+    selection: Option<Selection>,
+    drag: Option<Drag>,
+    undo_stack: Vec<Level>,
+    redo_stack: Vec<Level>,
+}
+
+impl Editor {
+    pub const DEFAULT_MASS: f32 = 3.6e17;
+    pub const DEFAULT_RADIUS: f32 = 10.0;
+    pub const MIN_RADIUS: f32 = 2.0;
+
+    pub fn new(level: Level) -> Editor {
+        Editor {
+            level,
+            selection: None,
+            drag: None,
+            undo_stack: vec![],
+            redo_stack: vec![],
+        }
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn into_level(self) -> Level {
+        self.level
+    }
+
+    pub fn selection(&self) -> Option<Selection> {
+        self.selection
+    }
+
+    pub fn selected_body(&self) -> Option<&BigMass> {
+        match self.selection {
+            Some(Selection::Body(i)) => self.level.bodies.get(i),
+            _ => None,
+        }
+    }
+
+    /// What lies under `pos`, preferring the hole and ball over bodies and
+    /// bodies placed later over earlier ones.
+    pub fn pick(&self, pos: na::Point2<f32>) -> Option<Selection> {
+        if na::distance(&pos, &self.level.hole.pos) <= self.level.hole.radius {
+            return Some(Selection::Hole);
+        }
+        if na::distance(&pos, &self.level.ball_start) <= Ball::RADIUS {
+            return Some(Selection::BallStart);
+        }
+        let positions = motion::positions_at(&self.level.bodies, 0.0);
+        self.level.bodies.iter()
+            .zip(positions)
+            .rposition(|(body, body_pos)| na::distance(&pos, &body_pos) <= body.radius)
+            .map(Selection::Body)
+    }
+
+    /// Selects whatever lies under `pos`, or clears the selection.
+    pub fn select_at(&mut self, pos: na::Point2<f32>) -> Option<Selection> {
+        self.selection = self.pick(pos);
+        self.selection
+    }
+
+    fn selection_pos(&self, selection: Selection) -> na::Point2<f32> {
+        match selection {
+            Selection::Body(i) => self.level.bodies[i].pos,
+            Selection::BallStart => self.level.ball_start,
+            Selection::Hole => self.level.hole.pos,
+        }
+    }
+
+    fn selection_pos_mut(&mut self, selection: Selection) -> &mut na::Point2<f32> {
+        match selection {
+            Selection::Body(i) => &mut self.level.bodies[i].pos,
+            Selection::BallStart => &mut self.level.ball_start,
+            Selection::Hole => &mut self.level.hole.pos,
+        }
+    }
+
+    /// Saves the current level so the next change can be undone.
+    fn checkpoint(&mut self) {
+        self.undo_stack.push(self.level.clone());
+        self.redo_stack.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            Some(level) => {
+                self.redo_stack.push(std::mem::replace(&mut self.level, level));
+                self.forget_stale_selection();
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some(level) => {
+                self.undo_stack.push(std::mem::replace(&mut self.level, level));
+                self.forget_stale_selection();
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn forget_stale_selection(&mut self) {
+        self.drag = None;
+        if let Some(Selection::Body(i)) = self.selection {
+            if i >= self.level.bodies.len() {
+                self.selection = None;
+            }
+        }
+    }
+
+    /// Adds a body with default mass and radius at `pos` and selects it.
+    pub fn place_body(&mut self, pos: na::Point2<f32>) {
+        self.checkpoint();
+        self.level.bodies.push(BigMass::new(pos, Self::DEFAULT_MASS, Self::DEFAULT_RADIUS));
+        self.selection = Some(Selection::Body(self.level.bodies.len() - 1));
+    }
+
+    pub fn delete_selected(&mut self) -> bool {
+        match self.selection {
+            Some(Selection::Body(i)) => {
+                self.checkpoint();
+                self.level.bodies.remove(i);
+                motion::remove_parent(&mut self.level.bodies, i, None);
+                self.selection = None;
+                self.drag = None;
+                true
+            },
+            // the ball start and hole are needed by every level
+            _ => false,
+        }
+    }
+
+    /// Grows the selected body or hole by `delta`, never below
+    /// [`Editor::MIN_RADIUS`].
+    pub fn resize_selected(&mut self, delta: f32) -> bool {
+        let selection = match self.selection {
+            Some(selection @ Selection::Body(_)) | Some(selection @ Selection::Hole) => selection,
+            _ => return false,
+        };
+        self.checkpoint();
+        let radius = match selection {
+            Selection::Body(i) => &mut self.level.bodies[i].radius,
+            _ => &mut self.level.hole.radius,
+        };
+        *radius = (*radius + delta).max(Self::MIN_RADIUS);
+        true
+    }
+
+    /// Multiplies the selected body's mass by `factor`.
+    pub fn scale_selected_mass(&mut self, factor: f32) -> bool {
+        match self.selection {
+            Some(Selection::Body(i)) => {
+                self.checkpoint();
+                self.level.bodies[i].mass *= factor;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    pub fn move_ball_start(&mut self, pos: na::Point2<f32>) {
+        self.checkpoint();
+        self.level.ball_start = pos;
+    }
+
+    pub fn move_hole(&mut self, pos: na::Point2<f32>) {
+        self.checkpoint();
+        self.level.hole.pos = pos;
+    }
+
+    /// Selects what lies under `pos` and starts dragging it.
+    pub fn begin_drag(&mut self, pos: na::Point2<f32>) -> bool {
+        match self.select_at(pos) {
+            Some(selection) => {
+                self.drag = Some(Drag {
+                    offset: self.selection_pos(selection) - pos,
+                    before: Some(self.level.clone()),
+                });
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Moves the dragged selection so it follows `pos`. The whole drag is
+    /// undone in one step.
+    pub fn drag_to(&mut self, pos: na::Point2<f32>) {
+        let (selection, drag) = match (self.selection, self.drag.as_mut()) {
+            (Some(selection), Some(drag)) => (selection, drag),
+            _ => return,
+        };
+        let offset = drag.offset;
+        if let Some(before) = drag.before.take() {
+            self.undo_stack.push(before);
+            self.redo_stack.clear();
+        }
+        *self.selection_pos_mut(selection) = pos + offset;
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), LevelError> {
+        level::save_level(path, &self.level)
+    }
+}
@@ -0,0 +1,51 @@
+use nalgebra as na;
+
+use crate::body::BigMass;
+use crate::world::{Ball, World};
+
+/// Gravity felt by the ball at one point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldSample {
+    pub pos: na::Point2<f32>,
+    /// Acceleration of the ball if it were here.
+    pub acc: na::Vector2<f32>,
+}
+
+/// Combined gravitational potential of `bodies` at `pos`.
+pub fn potential_at(bodies: &[BigMass], pos: na::Point2<f32>) -> f32 {
+    bodies.iter().map(|body| body.potential_at(pos)).sum()
+}
+
+/// Combined gravitational acceleration of `bodies` on the ball at `pos`.
+pub fn acceleration_at(bodies: &[BigMass], pos: na::Point2<f32>) -> na::Vector2<f32> {
+    bodies.iter().fold(na::Vector2::zeros(), |acc, body| {
+        acc + body.acceleration_at(pos, Ball::MASS)
+    })
+}
+
+/// Samples the gravity of `world` on a grid with `spacing` between points,
+/// covering the rectangle at `origin` of `size`. Samples are row-major and
+/// go through [`World::gravity_at`], so they use its quadtree if it has one.
+pub fn sample_grid(
+    world: &World,
+    origin: na::Point2<f32>,
+    size: na::Vector2<f32>,
+    spacing: f32,
+) -> Vec<FieldSample> {
+    let cols = (size.x / spacing).ceil().max(0.0) as usize + 1;
+    let rows = (size.y / spacing).ceil().max(0.0) as usize + 1;
+    // snap to the grid so samples stay put while the view moves
+    let start = na::Point2::new(
+        (origin.x / spacing).floor() * spacing,
+        (origin.y / spacing).floor() * spacing,
+    );
+
+    let mut samples = Vec::with_capacity(cols * rows);
+    for row in 0..rows {
+        for col in 0..cols {
+            let pos = start + na::Vector2::new(col as f32, row as f32) * spacing;
+            samples.push(FieldSample { pos, acc: world.gravity_at(pos) });
+        }
+    }
+    samples
+}
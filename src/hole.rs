@@ -0,0 +1,42 @@
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::world::Ball;
+
+/// The goal the ball has to be sunk into.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hole {
+    pub pos: na::Point2<f32>,
+    /// Distance from `pos` within which the ball can be captured.
+    #[serde(default = "Hole::default_radius")]
+    pub radius: f32,
+    /// Fastest the ball may travel and still drop in rather than skip over.
+    #[serde(default = "Hole::default_max_speed")]
+    pub max_speed: f32,
+}
+
+impl Hole {
+    pub const DEFAULT_RADIUS: f32 = 15.0;
+    pub const DEFAULT_MAX_SPEED: f32 = 300.0;
+
+    pub fn new(pos: na::Point2<f32>) -> Hole {
+        Hole {
+            pos,
+            radius: Self::DEFAULT_RADIUS,
+            max_speed: Self::DEFAULT_MAX_SPEED,
+        }
+    }
+
+    fn default_radius() -> f32 {
+        Self::DEFAULT_RADIUS
+    }
+
+    fn default_max_speed() -> f32 {
+        Self::DEFAULT_MAX_SPEED
+    }
+
+    pub fn captures(&self, ball: &Ball) -> bool {
+        na::distance(&ball.pos, &self.pos) <= self.radius
+            && ball.vel.magnitude() <= self.max_speed
+    }
+}
@@ -0,0 +1,164 @@
+//! Numerical schemes for moving the ball through the gravity field.
+//!
+//! Each scheme advances a position and velocity by one step under an
+//! acceleration that depends only on position, which is all gravity needs.
+//! They trade accuracy against how often they sample the field: explicit
+//! Euler once per step, semi-implicit Euler once, velocity Verlet twice and
+//! RK4 four times.
+
+use std::fmt;
+use std::str::FromStr;
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+/// Acceleration of the ball at a position.
+pub type Field<'a> = dyn Fn(na::Point2<f32>) -> na::Vector2<f32> + 'a;
+
+/// Advances a point mass by one step under `acc`.
+pub trait Integrator {
+    /// Position and velocity `dt` seconds after being at `pos` with `vel`.
+    fn step(
+        &self,
+        pos: na::Point2<f32>,
+        vel: na::Vector2<f32>,
+        dt: f32,
+        acc: &Field,
+    ) -> (na::Point2<f32>, na::Vector2<f32>);
+}
+
+/// Moves along the old velocity, then updates it. Steadily gains energy in
+/// an orbit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Euler;
+
+impl Integrator for Euler {
+    fn step(
+        &self,
+        pos: na::Point2<f32>,
+        vel: na::Vector2<f32>,
+        dt: f32,
+        acc: &Field,
+    ) -> (na::Point2<f32>, na::Vector2<f32>) {
+        (pos + vel * dt, vel + acc(pos) * dt)
+    }
+}
+
+/// Updates the velocity, then moves along it. Symplectic, so the energy of
+/// an orbit oscillates instead of drifting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn step(
+        &self,
+        pos: na::Point2<f32>,
+        vel: na::Vector2<f32>,
+        dt: f32,
+        acc: &Field,
+    ) -> (na::Point2<f32>, na::Vector2<f32>) {
+        let vel = vel + acc(pos) * dt;
+        (pos + vel * dt, vel)
+    }
+}
+
+/// Second order and symplectic, averaging the acceleration at both ends of
+/// the step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VelocityVerlet;
+
+impl Integrator for VelocityVerlet {
+    fn step(
+        &self,
+        pos: na::Point2<f32>,
+        vel: na::Vector2<f32>,
+        dt: f32,
+        acc: &Field,
+    ) -> (na::Point2<f32>, na::Vector2<f32>) {
+        let a = acc(pos);
+        let new_pos = pos + vel * dt + a * (0.5 * dt * dt);
+        (new_pos, vel + (a + acc(new_pos)) * (0.5 * dt))
+    }
+}
+
+/// Classic fourth order Runge-Kutta. The most accurate per step, though not
+/// symplectic, so its small error still drifts one way over long runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rk4;
+
+impl Integrator for Rk4 {
+    fn step(
+        &self,
+        pos: na::Point2<f32>,
+        vel: na::Vector2<f32>,
+        dt: f32,
+        acc: &Field,
+    ) -> (na::Point2<f32>, na::Vector2<f32>) {
+        let (p1, v1) = (vel, acc(pos));
+        let (p2, v2) = (vel + v1 * (0.5 * dt), acc(pos + p1 * (0.5 * dt)));
+        let (p3, v3) = (vel + v2 * (0.5 * dt), acc(pos + p2 * (0.5 * dt)));
+        let (p4, v4) = (vel + v3 * dt, acc(pos + p3 * dt));
+        (
+            pos + (p1 + p2 * 2.0 + p3 * 2.0 + p4) * (dt / 6.0),
+            vel + (v1 + v2 * 2.0 + v3 * 2.0 + v4) * (dt / 6.0),
+        )
+    }
+}
+
+/// Which [`Integrator`] a world moves its ball with, as stored in a level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Method {
+    Euler,
+    #[default]
+    SemiImplicitEuler,
+    VelocityVerlet,
+    Rk4,
+}
+
+impl Method {
+    pub const ALL: [Method; 4] = [
+        Method::Euler,
+        Method::SemiImplicitEuler,
+        Method::VelocityVerlet,
+        Method::Rk4,
+    ];
+
+    pub fn integrator(self) -> &'static dyn Integrator {
+        match self {
+            Method::Euler => &Euler,
+            Method::SemiImplicitEuler => &SemiImplicitEuler,
+            Method::VelocityVerlet => &VelocityVerlet,
+            Method::Rk4 => &Rk4,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Method::default()
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Method::Euler => write!(f, "euler"),
+            Method::SemiImplicitEuler => write!(f, "semi_implicit_euler"),
+            Method::VelocityVerlet => write!(f, "velocity_verlet"),
+            Method::Rk4 => write!(f, "rk4"),
+        }
+    }
+}
+
+impl FromStr for Method {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Method, String> {
+        Method::ALL.iter()
+            .copied()
+            .find(|method| method.to_string() == s)
+            .ok_or_else(|| format!(
+                "unknown integrator '{}', expected euler, semi_implicit_euler, velocity_verlet or rk4",
+                s
+            ))
+    }
+}
@@ -0,0 +1,234 @@
+//! Levels and their on-disk format.
+//!
+//! A level is stored as a JSON object:
+//!
+//! ```json
+//! {
+//!     "ball_start": [100.0, 300.0],
+//!     "bodies": [
+//!         {
+//!             "pos": [400.0, 300.0],
+//!             "mass": 3.6e17,
+//!             "radius": 30.0,
+//!             "material": { "restitution": 0.5, "friction": 0.2 }
+//!         },
+//!         {
+//!             "pos": [400.0, 150.0],
+//!             "mass": 1e16,
+//!             "radius": 8.0,
+//!             "motion": { "type": "orbit", "parent": 0, "eccentricity": 0.2 }
+//!         }
+//!     ],
+//!     "hole": { "pos": [600.0, 200.0], "radius": 15.0, "max_speed": 300.0 },
+//!     "par": 3,
+//!     "walls": [
+//!         { "a": [0.0, 0.0], "b": [800.0, 0.0] }
+//!     ],
+//!     "seed": 0
+//! }
+//! ```
+//!
+//! Points are `[x, y]` pairs in world units. A body's `material`, the hole's
+//! `radius` and `max_speed`, a wall's `material` and the whole `walls` list
+//! may be left out to get the defaults, as may `seed`, which is then 0.
+//!
+//! A body without a `motion` stays put. Moving bodies take one of
+//! `{ "type": "orbit", "parent", "eccentricity", "period", "phase" }`,
+//! `{ "type": "ping_pong", "offset", "period", "phase" }` or
+//! `{ "type": "circle", "center", "period", "phase" }`, see
+//! [`Motion`](crate::motion::Motion) for what each field means.
+//!
+//! `"drag"` slows the ball everywhere, as one of `{ "type": "none" }` for
+//! space levels, `{ "type": "linear", "coefficient" }` or
+//! `{ "type": "quadratic", "coefficient" }`. Left out, it is gentle linear
+//! drag with a coefficient of 1.5. A body can add quadratic drag
+//! around itself with `"atmosphere": { "height", "coefficient" }`.
+//!
+//! `"integrator"` picks how the ball is moved through the gravity field, one
+//! of `"euler"`, `"semi_implicit_euler"` (the default), `"velocity_verlet"`
+//! or `"rk4"`.
+//!
+//! Setting `"n_body": true` has the bodies without a `motion` pull on each
+//! other and move, starting from an optional `"vel": [x, y]`. With
+//! `"merge_bodies": true` as well, bodies that run into each other merge.
+//!
+//! `"asteroids"` lists rings of asteroids rolled from the level's `seed`,
+//! each as `{ "count", "center", "inner_radius", "outer_radius" }` with
+//! optional `"min_radius"`, `"max_radius"`, `"density"` and `"orbit"`, see
+//! [`AsteroidField`](crate::asteroids::AsteroidField). With many bodies,
+//! gravity is approximated by a quadtree tuned by
+//! `"barnes_hut": { "theta": 0.5, "threshold": 64 }`.
+//!
+//! `"obstacles"` lists shapes the ball bounces off along their edges, each
+//! `{ "type": "rectangle", "center", "size", "angle" }` or
+//! `{ "type": "polygon", "points" }` with at least 3 points, each with an
+//! optional `"material"`. A material with restitution above 1 makes a
+//! bumper. `"hollow": true` leaves only the outline, for a boundary around
+//! the course.
+//!
+//! `"rules"` sets what a shot may not do, costing `"penalty"` strokes (1 by
+//! default) and putting the ball back where it was shot from: leave
+//! `"bounds": { "min", "max" }`, stay in flight longer than `"time_limit"`
+//! seconds, or hit a body at `"crash_speed"` or faster.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::asteroids::{AsteroidField, Rng};
+use crate::barnes_hut::BarnesHut;
+use crate::body::BigMass;
+use crate::drag::Drag;
+use crate::hole::Hole;
+use crate::integrator::Method;
+use crate::obstacle::Obstacle;
+use crate::rules::Rules;
+use crate::wall::Wall;
+use crate::world::{Ball, World};
+
+/// Everything needed to set up a hole of a course.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Level {
+    pub ball_start: na::Point2<f32>,
+    pub bodies: Vec<BigMass>,
+    pub hole: Hole,
+    pub par: u32,
+    #[serde(default)]
+    pub walls: Vec<Wall>,
+    /// Seed for anything randomised about the level, kept in replays so a
+    /// run can be reproduced.
+    #[serde(default)]
+    pub seed: u64,
+    /// Drag slowing the ball everywhere, left out of the file when it is
+    /// the default.
+    #[serde(default, skip_serializing_if = "Drag::is_default")]
+    pub drag: Drag,
+    /// How the ball is moved through the gravity field, left out of the
+    /// file when it is the default.
+    #[serde(default, skip_serializing_if = "Method::is_default")]
+    pub integrator: Method,
+    /// Whether bodies pull on each other and move, rather than only on the
+    /// ball.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub n_body: bool,
+    /// Whether bodies that run into each other in an N-body level merge.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub merge_bodies: bool,
+    /// Fields of asteroids rolled from `seed` and added after `bodies`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub asteroids: Vec<AsteroidField>,
+    /// When gravity is approximated with a quadtree.
+    #[serde(default, skip_serializing_if = "BarnesHut::is_default")]
+    pub barnes_hut: BarnesHut,
+    /// Shapes in the ball's way besides `walls`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub obstacles: Vec<Obstacle>,
+    /// What costs penalty strokes and sends the ball back.
+    #[serde(default, skip_serializing_if = "Rules::is_default")]
+    pub rules: Rules,
+}
+
+impl Level {
+    /// The bodies placed in the level followed by its asteroids. Asteroids
+    /// keep clear of the ball, the hole, the placed bodies and each other.
+    pub fn all_bodies(&self) -> Vec<BigMass> {
+        let mut keep_clear = vec![
+            (self.ball_start, Ball::RADIUS * 2.0),
+            (self.hole.pos, self.hole.radius),
+        ];
+        keep_clear.extend(self.bodies.iter().map(|body| (body.pos, body.radius)));
+
+        let mut bodies = self.bodies.clone();
+        let mut rng = Rng::new(self.seed);
+        for field in self.asteroids.iter() {
+            let central_mass = self.bodies.iter()
+                .filter(|body| na::distance(&body.pos, &field.center) < field.inner_radius)
+                .map(|body| body.mass)
+                .sum();
+            let asteroids = field.generate(&mut rng, central_mass, &keep_clear);
+            keep_clear.extend(asteroids.iter().map(|asteroid| (asteroid.pos, asteroid.radius)));
+            bodies.extend(asteroids);
+        }
+        bodies
+    }
+
+    /// A fresh world with the ball at rest on the start position.
+    pub fn world(&self) -> World {
+        let mut world = World::new(self.ball_start, self.hole.clone());
+        world.drag = self.drag;
+        world.integrator = self.integrator;
+        world.n_body = self.n_body;
+        world.merge_bodies = self.merge_bodies;
+        world.set_barnes_hut(self.barnes_hut);
+        world.rules = self.rules;
+        world.set_bodies(self.all_bodies());
+        world.walls = self.walls.clone();
+        world.set_obstacles(self.obstacles.clone());
+        world
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    /// An obstacle has fewer than 3 corners, so it has no inside or edges.
+    OpenObstacle { obstacle: usize },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io(err) => write!(f, "couldn't access level file: {}", err),
+            LevelError::Format(err) => write!(f, "invalid level: {}", err),
+            LevelError::OpenObstacle { obstacle } => {
+                write!(f, "invalid level: obstacle {} needs at least 3 points", obstacle)
+            },
+        }
+    }
+}
+
+impl Error for LevelError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LevelError::Io(err) => Some(err),
+            LevelError::Format(err) => Some(err),
+            LevelError::OpenObstacle { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for LevelError {
+    fn from(err: io::Error) -> LevelError {
+        LevelError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for LevelError {
+    fn from(err: serde_json::Error) -> LevelError {
+        LevelError::Format(err)
+    }
+}
+
+pub fn parse_level(json: &str) -> Result<Level, LevelError> {
+    let level: Level = serde_json::from_str(json)?;
+    if let Some(obstacle) = level.obstacles.iter().position(|obstacle| !obstacle.is_closed()) {
+        return Err(LevelError::OpenObstacle { obstacle });
+    }
+    Ok(level)
+}
+
+pub fn load_level<P: AsRef<Path>>(path: P) -> Result<Level, LevelError> {
+    parse_level(&fs::read_to_string(path)?)
+}
+
+pub fn save_level<P: AsRef<Path>>(path: P, level: &Level) -> Result<(), LevelError> {
+    let json = serde_json::to_string_pretty(level)?;
+    fs::write(path, json)?;
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Headless gravity golf simulation.
+//!
+//! Everything in here is plain data and math with no dependency on ggez, so
+//! the physics can be driven from tests or tools without opening a window.
+//! The game binary is a thin front end over [`World`], built with the
+//! default `game` feature. Building with `--no-default-features` leaves out
+//! ggez entirely, so the library, its tests and `gulf-sim` build and run on
+//! machines without a display or audio libraries.
+
+pub mod asteroids;
+pub mod barnes_hut;
+pub mod body;
+pub mod camera;
+pub mod collision;
+pub mod difficulty;
+pub mod drag;
+pub mod editor;
+pub mod field;
+pub mod hole;
+pub mod integrator;
+pub mod level;
+pub mod motion;
+pub mod nbody;
+pub mod obstacle;
+pub mod replay;
+pub mod rules;
+pub mod score;
+pub mod shot;
+pub mod solver;
+pub mod trail;
+pub mod verify;
+pub mod wall;
+pub mod world;
+
+pub use asteroids::AsteroidField;
+pub use barnes_hut::BarnesHut;
+pub use body::{BigMass, Material};
+pub use camera::Camera;
+pub use collision::{Collider, Collision};
+pub use difficulty::Difficulty;
+pub use drag::{Atmosphere, Drag};
+pub use editor::{Editor, Selection};
+pub use hole::Hole;
+pub use integrator::{Integrator, Method};
+pub use level::{load_level, parse_level, save_level, Level, LevelError};
+pub use motion::Motion;
+pub use obstacle::{Obstacle, Shape};
+pub use replay::{level_hash, Replay, ReplayError, ShotRecord};
+pub use rules::{Bounds, Fault, Rules};
+pub use score::{HoleScore, ScoreLabel, Scorecard};
+pub use shot::Shot;
+pub use solver::{solve, Solution, SolverConfig};
+pub use trail::{Trail, TrailPoint};
+pub use wall::Wall;
+pub use world::{Ball, World};
@@ -1,4 +1,3 @@
-use ggez;
 use ggez::input::{
     mouse::MouseButton,
     keyboard::{
@@ -7,60 +6,541 @@ use ggez::input::{
     }
 };
 use ggez::event;
+use ggez::timer;
 use ggez::graphics::{self, DrawParam};
 use ggez::nalgebra as na;
 
-#[derive(Debug)]
-struct BigMass {
-    mass: f32,
-    radius: f32,
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use gulf::{BigMass, Camera, Difficulty, Editor, Level, Replay, Scorecard, Selection, Shot, Trail, World};
+use gulf::field;
+use gulf::solver::{self, SolverConfig};
+
+/// Arrow grid of the gravity field in world coordinates, kept until the
+/// bodies change, the zoom changes the arrow spacing or the view leaves the
+/// area it covers. Bodies moving on their own only get it resampled every
+/// [`MainState::FIELD_REFRESH_TICKS`].
+struct FieldOverlay {
+    bodies: Vec<BigMass>,
+    /// World tick the field was sampled on.
+    tick: u64,
+    spacing: f32,
+    /// Origin and size of the area sampled.
+    area: (na::Point2<f32>, na::Vector2<f32>),
+    mesh: Option<graphics::Mesh>,
 }
 
-impl BigMass {
-    fn gravity(&self) -> f32 {
-        6.674e-11 * self.mass * self.radius.powi(2)
+impl FieldOverlay {
+    fn covers(&self, (origin, size): (na::Point2<f32>, na::Vector2<f32>)) -> bool {
+        let (area_origin, area_size) = self.area;
+        let end = origin + size;
+        let area_end = area_origin + area_size;
+        origin.x >= area_origin.x && origin.y >= area_origin.y && end.x <= area_end.x && end.y <= area_end.y
+    }
+}
+
+/// The solver looking for a hint on its own thread. Dropping it stops the
+/// search, so abandoned searches don't pile up.
+struct HintSearch {
+    cancel: Arc<AtomicBool>,
+    /// Only taken when joining it.
+    thread: Option<thread::JoinHandle<Option<Shot>>>,
+}
+
+impl HintSearch {
+    fn start(world: World, config: SolverConfig) -> HintSearch {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let config = SolverConfig { cancel: Some(cancel.clone()), ..config };
+        let thread = thread::spawn(move || {
+            solver::best_shot(&world, &config).map(|outcome| outcome.shot)
+        });
+        HintSearch { cancel, thread: Some(thread) }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_some_and(thread::JoinHandle::is_finished)
+    }
+
+    /// The shot found, waiting for the search to finish if it hasn't.
+    fn join(mut self) -> Option<Shot> {
+        self.thread.take().and_then(|thread| thread.join().ok().flatten())
+    }
+}
+
+impl Drop for HintSearch {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
     }
 }
 
 struct MainState {
-    ball_pos: na::Point2<f32>,
-    bodies: Vec<(na::Point2<f32>, BigMass)>,
+    tick_rate: u32,
+    difficulty: Difficulty,
+    course: Vec<Level>,
+    level_index: usize,
+    world: World,
+    prev_ball_pos: na::Point2<f32>,
+    camera: Camera,
+    /// Held while dragging the view with the right mouse button.
+    panning: bool,
     anchored: bool,
+    /// Cursor in window pixels.
+    screen_mouse_pos: na::Point2<f32>,
+    /// Cursor in world coordinates.
     mouse_pos: na::Point2<f32>,
-    cur_vel: f32,
+    level_complete: bool,
+    strokes: u32,
+    scorecard: Scorecard,
+    /// Set while the current level is being edited instead of played.
+    editor: Option<Editor>,
+    save_path: PathBuf,
+    /// Feedback from the last editor action, shown in the HUD.
+    status: Option<String>,
+    show_field: bool,
+    field_overlay: Option<FieldOverlay>,
+    /// Shots taken on the current level so far.
+    replay: Replay,
+    /// Set when a recorded replay drives the ball instead of the player.
+    playback: Option<Replay>,
+    /// Best shot the solver found from the ball's current position.
+    hint: Option<Shot>,
+    /// Solver looking for `hint` on its own thread, so the game doesn't
+    /// freeze while it searches. Dropped, stopping it, once the ball is no
+    /// longer where it was asked about.
+    hint_search: Option<HintSearch>,
 }
 
 impl MainState {
 
-    const BALL_MASS: f32 = 2.0;
+    /// Converts the aiming drag (in pixels) into an impulse per second.
+    const LAUNCH_SCALE: f32 = 8.0;
+    const DEFAULT_TICK_RATE: u32 = 60;
+    /// Mass multiplier for one notch of the scroll wheel in the editor.
+    const MASS_SCROLL_FACTOR: f32 = 1.25;
+    /// Radius change for one press of +/- in the editor.
+    const RESIZE_STEP: f32 = 2.0;
+    /// Every how many ticks of the predicted trajectory a dot is drawn.
+    const PREVIEW_DOT_SPACING: usize = 3;
+    /// Speed at which the trail is drawn fully in its fast color.
+    const TRAIL_FAST_SPEED: f32 = 3000.0;
+    const TRAIL_WIDTH: f32 = 4.0;
+    /// Distance between field arrows in window pixels.
+    const FIELD_SPACING: f32 = 40.0;
+    /// Acceleration at which field arrows are drawn at full length.
+    const FIELD_STRONG: f32 = 5000.0;
+    /// Ticks between resampling the field while bodies move.
+    const FIELD_REFRESH_TICKS: u64 = 15;
+    /// Zoom multiplier for one notch of the scroll wheel.
+    const ZOOM_SCROLL_FACTOR: f32 = 1.1;
     
-    fn new() -> ggez::GameResult<MainState> {
+    fn new(
+        ctx: &ggez::Context,
+        tick_rate: u32,
+        difficulty: Difficulty,
+        course: Vec<Level>,
+        save_path: PathBuf
+    ) -> ggez::GameResult<MainState> {
+        let world = course.first()
+            .ok_or_else(|| ggez::GameError::ConfigError("the course has no levels".to_string()))?
+            .world();
+        let (screen_w, screen_h) = graphics::drawable_size(ctx);
         Ok(MainState {
-            ball_pos: na::Point2::new(50.0, 50.0),
-            bodies: vec![],
+            tick_rate,
+            difficulty,
+            level_index: 0,
+            prev_ball_pos: world.ball.pos,
+            world,
+            camera: Camera::new(na::Point2::new(screen_w / 2.0, screen_h / 2.0)),
+            panning: false,
             anchored: false,
+            screen_mouse_pos: na::Point2::new(0.0, 0.0),
             mouse_pos: na::Point2::new(0.0, 0.0),
-            cur_vel: 0.0
+            level_complete: false,
+            strokes: 0,
+            scorecard: Scorecard::new(),
+            editor: None,
+            save_path,
+            status: None,
+            show_field: difficulty.shows_field(),
+            field_overlay: None,
+            replay: Replay::new(&course[0], tick_rate),
+            playback: None,
+            hint: None,
+            hint_search: None,
+            course,
         })
     }
 
+    fn level(&self) -> &Level {
+        &self.course[self.level_index]
+    }
+
+    fn is_last_level(&self) -> bool {
+        self.level_index + 1 == self.course.len()
+    }
+
+    /// Moves on to the next level of the course, if there is one.
+    fn next_level(&mut self) {
+        if self.is_last_level() {
+            return;
+        }
+        self.level_index += 1;
+        self.restart_level();
+    }
+
+    /// Puts the ball back on the start of the current level.
+    fn restart_level(&mut self) {
+        self.world = self.level().world();
+        self.prev_ball_pos = self.world.ball.pos;
+        self.anchored = false;
+        self.level_complete = false;
+        self.strokes = 0;
+        self.replay = Replay::new(self.level(), self.tick_rate);
+        self.hint = None;
+        self.hint_search = None;
+    }
+
+    /// Starts the solver looking for the best shot from where the ball lies.
+    fn show_hint(&mut self) {
+        if self.world.ball.is_moving() || self.level_complete || self.hint_search.is_some() {
+            return;
+        }
+        let config = SolverConfig {
+            tick_rate: self.tick_rate,
+            ..SolverConfig::default()
+        };
+        self.hint_search = Some(HintSearch::start(self.world.clone(), config));
+        self.status = Some("Looking for a shot...".to_string());
+    }
+
+    /// Takes the hint once the solver has found it.
+    fn poll_hint(&mut self) {
+        if !self.hint_search.as_ref().is_some_and(|search| search.is_finished()) {
+            return;
+        }
+        if let Some(search) = self.hint_search.take() {
+            self.hint = search.join();
+            self.status = match self.hint {
+                Some(_) => None,
+                None => Some("No shot found".to_string()),
+            };
+        }
+    }
+
+    /// Plays `replay` back on the current level instead of taking input,
+    /// handing control back to the player once it ends.
+    fn start_playback(&mut self, replay: Replay) {
+        self.tick_rate = replay.tick_rate;
+        self.restart_level();
+        self.status = Some("Playing back replay".to_string());
+        self.playback = Some(replay);
+    }
+
+    /// Steps the world by one tick, letting the replay take its shots when
+    /// playing one back.
+    fn tick(&mut self, dt: f32) {
+        self.prev_ball_pos = self.world.ball.pos;
+        match self.playback.take() {
+            Some(replay) => {
+                if !replay.is_done(&self.world) {
+                    self.strokes += replay.shots_at(self.world.tick()).count() as u32;
+                    replay.step(&mut self.world);
+                }
+                if replay.is_done(&self.world) {
+                    self.status = Some(match replay.check(&self.world) {
+                        Ok(()) => "Replay verified".to_string(),
+                        Err(err) => format!("Replay mismatch: {}", err),
+                    });
+                    // play on from where it ended, recording on top of it
+                    self.replay = replay;
+                } else {
+                    self.playback = Some(replay);
+                }
+            },
+            None => self.world.step(dt),
+        }
+        if let Some(fault) = self.world.fault {
+            let penalty = self.world.rules.penalty;
+            self.strokes += penalty;
+            self.status = Some(format!("{}! +{} penalty", fault, penalty));
+        }
+    }
+
+    fn save_replay(&mut self) {
+        const REPLAY_PATH: &str = "replay.json";
+
+        let mut replay = self.replay.clone();
+        replay.finish(&self.world);
+        self.status = Some(match replay.save(REPLAY_PATH) {
+            Ok(()) => format!("Replay saved to {}", REPLAY_PATH),
+            Err(err) => format!("Couldn't save replay: {}", err),
+        });
+    }
+
+    fn toggle_editor(&mut self) {
+        match self.editor.take() {
+            Some(editor) => {
+                self.course[self.level_index] = editor.into_level();
+                self.restart_level();
+            },
+            None => {
+                self.editor = Some(Editor::new(self.level().clone()));
+                self.sync_editor_world();
+            },
+        }
+        self.status = None;
+    }
+
+    /// Shows the level being edited as it currently is.
+    fn sync_editor_world(&mut self) {
+        if let Some(editor) = &self.editor {
+            self.world = editor.level().world();
+            self.prev_ball_pos = self.world.ball.pos;
+            self.anchored = false;
+            self.level_complete = false;
+            self.hint = None;
+            self.hint_search = None;
+        }
+    }
+
+    fn editor_key(&mut self, keycode: KeyCode, keymods: KeyMods) {
+        let editor = match self.editor.as_mut() {
+            Some(editor) => editor,
+            None => return,
+        };
+        let ctrl = keymods.contains(KeyMods::CTRL);
+        match keycode {
+            KeyCode::M => editor.place_body(self.mouse_pos),
+            KeyCode::B => editor.move_ball_start(self.mouse_pos),
+            KeyCode::G => editor.move_hole(self.mouse_pos),
+            KeyCode::Delete | KeyCode::Back => {
+                editor.delete_selected();
+            },
+            KeyCode::Equals | KeyCode::Add => {
+                editor.resize_selected(Self::RESIZE_STEP);
+            },
+            KeyCode::Minus | KeyCode::Subtract => {
+                editor.resize_selected(-Self::RESIZE_STEP);
+            },
+            KeyCode::Z if ctrl && keymods.contains(KeyMods::SHIFT) => {
+                editor.redo();
+            },
+            KeyCode::Z if ctrl => {
+                editor.undo();
+            },
+            KeyCode::Y if ctrl => {
+                editor.redo();
+            },
+            KeyCode::S if ctrl => {
+                self.status = Some(match editor.save(&self.save_path) {
+                    Ok(()) => format!("Saved to {}", self.save_path.display()),
+                    Err(err) => format!("Couldn't save: {}", err),
+                });
+            },
+            _ => return,
+        }
+        self.sync_editor_world();
+    }
+
+    fn hud_text(&self) -> String {
+        match &self.editor {
+            Some(editor) => {
+                let mut text = "Editor  M: place  drag: move  Del: delete  +/-: size  wheel: mass\n\
+                    B: ball start  G: hole  Ctrl+Z/Y: undo/redo  Ctrl+S: save  F: field  E: play".to_string();
+                if let Some(body) = editor.selected_body() {
+                    text += &format!("\nmass {:.3e}  radius {:.1}", body.mass, body.radius);
+                }
+                if let Some(status) = &self.status {
+                    text += &format!("\n{}", status);
+                }
+                text
+            },
+            None => {
+                let mut text = format!(
+                    "Hole {}/{}  Par {}  Strokes {}  E: edit  D: difficulty ({})  F: field  T: export trail  R: save replay  H: hint",
+                    self.level_index + 1,
+                    self.course.len(),
+                    self.level().par,
+                    self.strokes,
+                    self.difficulty
+                );
+                if let Some(status) = &self.status {
+                    text += &format!("\n{}", status);
+                }
+                text
+            },
+        }
+    }
+
+    fn export_trail(&mut self) {
+        const TRAIL_PATH: &str = "trail.json";
+
+        let result = self.world.trail.to_json()
+            .map_err(|err| err.to_string())
+            .and_then(|json| std::fs::write(TRAIL_PATH, json).map_err(|err| err.to_string()));
+        self.status = Some(match result {
+            Ok(()) => format!("Trail exported to {}", TRAIL_PATH),
+            Err(err) => format!("Couldn't export trail: {}", err),
+        });
+    }
+
+    /// Builds the trail as a single ribbon whose color shows the speed of the
+    /// ball and which fades out with age.
+    fn trail_mesh(ctx: &mut ggez::Context, trail: &Trail, now: f32) -> ggez::GameResult<Option<graphics::Mesh>> {
+        const EPSILON: f32 = 1e-4;
+
+        let points: Vec<_> = trail.points().collect();
+        if points.len() < 2 {
+            return Ok(None);
+        }
+
+        let mut verts = Vec::with_capacity(points.len() * 2);
+        let mut normal = na::Vector2::new(0.0, 1.0);
+        for (i, point) in points.iter().enumerate() {
+            let prev = points[i.saturating_sub(1)].pos;
+            let next = points[(i + 1).min(points.len() - 1)].pos;
+            if let Some(dir) = (next - prev).try_normalize(EPSILON) {
+                normal = na::Vector2::new(-dir.y, dir.x);
+            }
+
+            let fast = (point.speed / Self::TRAIL_FAST_SPEED).min(1.0);
+            let fade = 1.0 - ((now - point.time) / trail.duration).min(1.0);
+            let color = [0.3 + 0.7 * fast, 0.6 - 0.3 * fast, 1.0 - 0.8 * fast, fade];
+            let offset = normal * Self::TRAIL_WIDTH / 2.0;
+            for side in [offset, -offset].iter() {
+                let pos = point.pos + side;
+                verts.push(graphics::Vertex {
+                    pos: [pos.x, pos.y],
+                    uv: [0.0, 0.0],
+                    color,
+                });
+            }
+        }
+        let indices: Vec<u32> = (0..points.len() as u32 - 1)
+            .flat_map(|i| {
+                let v = i * 2;
+                vec![v, v + 1, v + 2, v + 1, v + 3, v + 2]
+            })
+            .collect();
+
+        let mesh = graphics::MeshBuilder::new()
+            .raw(&verts, &indices, None)
+            .build(ctx)?;
+        Ok(Some(mesh))
+    }
+
+    /// Arrow grid of the field of `bodies` covering the part of the world in
+    /// `view`, reusing the last one while the bodies and zoom stay the same
+    /// and the view stays inside it.
+    fn field_mesh(&mut self, ctx: &mut ggez::Context, view: (na::Point2<f32>, na::Vector2<f32>)) -> ggez::GameResult<Option<&graphics::Mesh>> {
+        let spacing = Self::FIELD_SPACING / self.camera.zoom;
+        let stale = match &self.field_overlay {
+            Some(overlay) => {
+                // bodies changed without the world stepping were edited or
+                // restarted, which shows straight away
+                let since = self.world.tick().saturating_sub(overlay.tick);
+                let bodies_changed = overlay.bodies != self.world.bodies()
+                    && (since == 0 || since >= Self::FIELD_REFRESH_TICKS);
+                overlay.spacing != spacing || bodies_changed || !overlay.covers(view)
+            },
+            None => true,
+        };
+        if stale {
+            // half a view of margin all round, so following the ball or
+            // panning doesn't need a new grid straight away
+            let area = (view.0 - view.1 / 2.0, view.1 * 2.0);
+            let samples = field::sample_grid(&self.world, area.0, area.1, spacing);
+            let mut arrows = graphics::MeshBuilder::new();
+            let mut any = false;
+            for sample in samples.iter() {
+                let magnitude = sample.acc.magnitude();
+                if magnitude <= 0.0 {
+                    continue;
+                }
+                let strength = (magnitude / Self::FIELD_STRONG).sqrt().min(1.0);
+                let dir = sample.acc / magnitude;
+                let tip = sample.pos + dir * spacing * 0.8 * strength.max(0.2);
+                let side = na::Vector2::new(-dir.y, dir.x) * spacing * 0.1;
+                let back = tip - dir * spacing * 0.15;
+                let color = [0.6, 0.9, 0.6, 0.2 + 0.6 * strength].into();
+                let width = 1.0 / self.camera.zoom;
+                arrows.line(&[sample.pos, tip], width, color)?;
+                arrows.line(&[back + side, tip, back - side], width, color)?;
+                any = true;
+            }
+            self.field_overlay = Some(FieldOverlay {
+                bodies: self.world.bodies().to_vec(),
+                tick: self.world.tick(),
+                spacing,
+                area,
+                mesh: if any { Some(arrows.build(ctx)?) } else { None },
+            });
+        }
+        Ok(self.field_overlay.as_ref().and_then(|overlay| overlay.mesh.as_ref()))
+    }
+
+    fn result_text(&self) -> String {
+        let mut text = match self.scorecard.holes.last() {
+            Some(score) => format!("{}! {} strokes, par {}\n", score.label(), score.strokes, score.par),
+            None => String::new(),
+        };
+        if self.is_last_level() {
+            text += &format!(
+                "Course complete: {} strokes, par {} ({:+})",
+                self.scorecard.total_strokes(),
+                self.scorecard.total_par(),
+                self.scorecard.to_par()
+            );
+        } else {
+            text += "Click to play the next hole";
+        }
+        text
+    }
+
+    /// Recomputes the world position under the cursor, for when either the
+    /// cursor or the camera has moved.
+    fn update_mouse_pos(&mut self, ctx: &ggez::Context) {
+        let viewport = graphics::drawable_size(ctx);
+        self.mouse_pos = self.camera.screen_to_world(self.screen_mouse_pos, viewport);
+        if let Some(editor) = self.editor.as_mut() {
+            if editor.is_dragging() {
+                editor.drag_to(self.mouse_pos);
+                self.sync_editor_world();
+            }
+        }
+    }
+
     fn get_forward(&self) -> na::Vector2<f32> {
-        self.ball_pos - self.mouse_pos
+        self.world.ball.pos - self.mouse_pos
+    }
+
+    /// Ball position blended between the last two ticks by `alpha`.
+    fn interpolated_ball_pos(&self, alpha: f32) -> na::Point2<f32> {
+        self.prev_ball_pos + (self.world.ball.pos - self.prev_ball_pos) * alpha
     }
 }
 
 impl event::EventHandler for MainState {
-    fn update(&mut self, _ctx: &mut ggez::Context) -> ggez::GameResult {
-        const EPSILON: f32 = 1e-2;
-
-        if self.cur_vel > EPSILON {
-            let displ_vec = self.get_forward().normalize() * self.cur_vel;
-            let displ_vec = self.bodies.iter().fold(displ_vec, |displ_vec, body| {
-                let body_dir = body.0 - self.ball_pos;
-                displ_vec + body_dir.normalize() * body.1.gravity()
-            });
-            self.ball_pos += displ_vec;
-            self.cur_vel /= 2.0;
+    fn update(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        let dt = 1.0 / self.tick_rate as f32;
+        while timer::check_update_time(ctx, self.tick_rate) {
+            if self.editor.is_some() {
+                continue;
+            }
+            self.tick(dt);
+            if self.world.ball.is_moving() && !self.panning {
+                self.camera.follow(self.world.ball.pos, dt);
+            }
+        }
+        self.update_mouse_pos(ctx);
+        self.poll_hint();
+        if self.world.is_sunk() && !self.level_complete {
+            self.level_complete = true;
+            self.anchored = false;
+            self.scorecard.record(self.level().par, self.strokes);
         }
         Ok(())
     }
@@ -68,36 +548,176 @@ impl event::EventHandler for MainState {
     fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
         graphics::clear(ctx, [0.1, 0.2, 0.3, 1.0].into());
 
+        let viewport = graphics::drawable_size(ctx);
+        let (view_origin, view_size) = self.camera.view(viewport);
+        graphics::set_screen_coordinates(
+            ctx,
+            graphics::Rect::new(view_origin.x, view_origin.y, view_size.x, view_size.y)
+        )?;
+
+        if self.show_field {
+            if let Some(mesh) = self.field_mesh(ctx, (view_origin, view_size))? {
+                graphics::draw(ctx, mesh, DrawParam::default())?;
+            }
+        }
+
+        if let Some(bounds) = self.world.rules.bounds {
+            let size = bounds.max - bounds.min;
+            let outline = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::stroke(2.0),
+                graphics::Rect::new(bounds.min.x, bounds.min.y, size.x, size.y),
+                [1.0, 0.3, 0.3, 0.6].into()
+            )?;
+            graphics::draw(ctx, &outline, DrawParam::default())?;
+        }
+
+        let hole = &self.world.hole;
+        let hole_disc = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            hole.pos,
+            hole.radius,
+            1.0,
+            [0.0, 0.0, 0.0, 1.0].into()
+        )?;
+        graphics::draw(ctx, &hole_disc, DrawParam::default())?;
+
+        if let Some(trail) = Self::trail_mesh(ctx, &self.world.trail, self.world.time())? {
+            graphics::draw(ctx, &trail, DrawParam::default())?;
+        }
+
+        let dt = 1.0 / self.tick_rate as f32;
+        let alpha = timer::duration_to_f64(timer::remaining_update_time(ctx)) as f32 / dt;
         let ball_disc = graphics::Mesh::new_circle(
             ctx,
             graphics::DrawMode::fill(),
-            self.ball_pos,
-            10.0,
+            self.interpolated_ball_pos(alpha.min(1.0)),
+            gulf::Ball::RADIUS,
             2.0,
             [1.0, 1.0, 1.0, 1.0].into()
         )?;
         graphics::draw(ctx, &ball_disc, DrawParam::default())?;
 
-        if self.anchored && self.ball_pos != self.mouse_pos {
+        let ball_pos = self.world.ball.pos;
+        if let Some(hint) = self.hint {
+            // drawn like the aiming arrow for the drag that makes this shot
+            let forward = hint.force() / Self::LAUNCH_SCALE;
+            let arrow = graphics::Mesh::new_line(
+                ctx,
+                &[ball_pos - forward, ball_pos + forward],
+                2.0,
+                [0.3, 1.0, 0.4, 0.8].into()
+            )?;
+            graphics::draw(ctx, &arrow, DrawParam::default())?;
+        }
+
+        if self.anchored && ball_pos != self.mouse_pos {
             let arrow = graphics::Mesh::new_line(
                 ctx, 
-                &[self.mouse_pos, self.ball_pos + self.get_forward()], 
+                &[self.mouse_pos, ball_pos + self.get_forward()], 
                 2.0,
                 [1.0, 1.0, 1.0, 1.0].into()
             )?;
             graphics::draw(ctx, &arrow, DrawParam::default())?;
+
+            let path = self.world.predict(
+                self.get_forward() * Self::LAUNCH_SCALE,
+                self.difficulty.preview_ticks(),
+                dt
+            );
+            if path.len() >= Self::PREVIEW_DOT_SPACING {
+                let mut dots = graphics::MeshBuilder::new();
+                for &pos in path.iter().skip(Self::PREVIEW_DOT_SPACING - 1).step_by(Self::PREVIEW_DOT_SPACING) {
+                    dots.circle(
+                        graphics::DrawMode::fill(),
+                        pos,
+                        2.0,
+                        1.0,
+                        [1.0, 1.0, 1.0, 0.6].into()
+                    );
+                }
+                let dots = dots.build(ctx)?;
+                graphics::draw(ctx, &dots, DrawParam::default())?;
+            }
+        }
+
+        if !self.world.bodies().is_empty() {
+            // one mesh for all of them, asteroid fields can have thousands
+            let mut discs = graphics::MeshBuilder::new();
+            for body in self.world.bodies().iter() {
+                discs.circle(
+                    graphics::DrawMode::fill(),
+                    body.pos,
+                    body.radius,
+                    2.0,
+                    [1.0, 0.5, 0.3, 1.0].into()
+                );
+            }
+            let discs = discs.build(ctx)?;
+            graphics::draw(ctx, &discs, DrawParam::default())?;
         }
 
-        for body in self.bodies.iter() {
-            let body_disc = graphics::Mesh::new_circle(
+        let solid: Vec<_> = self.world.obstacles().iter()
+            .filter(|obstacle| !obstacle.hollow)
+            .map(|obstacle| (obstacle.points(), obstacle.material.restitution > 1.0))
+            .filter(|(points, _)| points.len() >= 3)
+            .collect();
+        if !solid.is_empty() {
+            let mut fills = graphics::MeshBuilder::new();
+            for (points, bumper) in solid.iter() {
+                let color = if *bumper { [0.9, 0.3, 0.6, 1.0] } else { [0.5, 0.5, 0.55, 1.0] };
+                fills.polygon(graphics::DrawMode::fill(), points, color.into())?;
+            }
+            let fills = fills.build(ctx)?;
+            graphics::draw(ctx, &fills, DrawParam::default())?;
+        }
+
+        // obstacle edges are drawn like walls, over their fill
+        for wall in self.world.walls.iter().chain(self.world.obstacle_edges()) {
+            let wall_line = graphics::Mesh::new_line(
                 ctx,
-                graphics::DrawMode::fill(),
-                body.0,
-                body.1.radius,
-                2.0,
-                [1.0, 0.5, 0.3, 1.0].into()
+                &[wall.a, wall.b],
+                4.0,
+                [0.8, 0.8, 0.8, 1.0].into()
             )?;
-            graphics::draw(ctx, &body_disc, DrawParam::default())?;
+            graphics::draw(ctx, &wall_line, DrawParam::default())?;
+        }
+
+        if let Some(editor) = &self.editor {
+            let selected = match editor.selection() {
+                Some(Selection::Body(i)) => Some((self.world.bodies()[i].pos, self.world.bodies()[i].radius)),
+                Some(Selection::BallStart) => Some((self.world.ball.pos, gulf::Ball::RADIUS)),
+                Some(Selection::Hole) => Some((self.world.hole.pos, self.world.hole.radius)),
+                None => None,
+            };
+            if let Some((pos, radius)) = selected {
+                let outline = graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::stroke(2.0),
+                    pos,
+                    radius + 4.0,
+                    1.0,
+                    [1.0, 1.0, 0.0, 1.0].into()
+                )?;
+                graphics::draw(ctx, &outline, DrawParam::default())?;
+            }
+        }
+
+        graphics::set_screen_coordinates(ctx, graphics::Rect::new(0.0, 0.0, viewport.0, viewport.1))?;
+
+        let hud = graphics::Text::new(self.hud_text());
+        graphics::draw(ctx, &hud, DrawParam::default().dest(na::Point2::new(10.0, 10.0)))?;
+
+        if self.level_complete {
+            let text = graphics::Text::new(self.result_text());
+            let (w, h) = viewport;
+            let (text_w, text_h) = text.dimensions(ctx);
+            let pos = na::Point2::new(
+                (w - text_w as f32) / 2.0,
+                (h - text_h as f32) / 2.0
+            );
+            graphics::draw(ctx, &text, DrawParam::default().dest(pos))?;
         }
 
         graphics::present(ctx)?;
@@ -107,63 +727,187 @@ impl event::EventHandler for MainState {
     fn mouse_button_down_event(
         &mut self,
         _ctx: &mut ggez::Context,
-        _button: MouseButton,
+        button: MouseButton,
         _x: f32,
         _y: f32
     ) {
-        self.anchored = true;
+        if button == MouseButton::Right {
+            self.panning = true;
+        } else if self.playback.is_some() {
+            // the replay is in control
+        } else if let Some(editor) = self.editor.as_mut() {
+            editor.begin_drag(self.mouse_pos);
+        } else if self.level_complete {
+            self.next_level();
+        } else if !self.world.ball.is_moving() {
+            // shots are only taken from where the ball lies
+            self.anchored = true;
+        }
     }
 
     fn mouse_button_up_event(
         &mut self,
         _ctx: &mut ggez::Context,
-        _button: MouseButton,
+        button: MouseButton,
         _x: f32,
         _y: f32
     ) {
-        // F = ma, we take F = length of forward
-        // we apply a = F / m instantaneouly to give velocity
-        let force = self.get_forward().magnitude();
-        self.cur_vel = force / Self::BALL_MASS;
+        if button == MouseButton::Right {
+            self.panning = false;
+            return;
+        }
+        if let Some(editor) = self.editor.as_mut() {
+            editor.end_drag();
+            return;
+        }
+        if !self.anchored {
+            return;
+        }
+        // we take F = forward, the direction is fixed here so moving the
+        // mouse afterwards doesn't steer the ball
+        let shot = Shot::from_force(self.get_forward() * Self::LAUNCH_SCALE);
+        self.replay.record(self.world.tick(), shot);
+        self.world.shoot(shot.force());
+        self.hint = None;
+        self.hint_search = None;
+        self.strokes += 1;
         self.anchored = false;
     }
 
     fn mouse_motion_event(
         &mut self,
-        _ctx: &mut ggez::Context,
+        ctx: &mut ggez::Context,
         x: f32,
         y: f32,
-        _dx: f32,
-        _dy: f32
+        dx: f32,
+        dy: f32
     ) {
-        self.mouse_pos = na::Point2::new(x, y);
+        if self.panning {
+            self.camera.pan(na::Vector2::new(dx, dy));
+        }
+        self.screen_mouse_pos = na::Point2::new(x, y);
+        self.update_mouse_pos(ctx);
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut ggez::Context, _x: f32, y: f32) {
+        if y == 0.0 {
+            return;
+        }
+        if let Some(editor) = self.editor.as_mut() {
+            if editor.scale_selected_mass(Self::MASS_SCROLL_FACTOR.powf(y)) {
+                self.sync_editor_world();
+                return;
+            }
+        }
+        let viewport = graphics::drawable_size(ctx);
+        self.camera.zoom_at(Self::ZOOM_SCROLL_FACTOR.powf(y), self.screen_mouse_pos, viewport);
+        self.update_mouse_pos(ctx);
     }
 
     fn key_down_event(
         &mut self,
-        ctx: &mut ggez::Context,
+        _ctx: &mut ggez::Context,
         keycode: KeyCode,
-        _keymods: KeyMods,
+        keymods: KeyMods,
         _repeat: bool
     ) {
         match keycode {
-            KeyCode::M => {
-                self.bodies.push((
-                    self.mouse_pos,
-                    BigMass {
-                        mass: 1e9,
-                        radius: 10.0,
-                    }
-                ));
+            _ if self.playback.is_some() => (),
+            KeyCode::E => self.toggle_editor(),
+            KeyCode::D if self.editor.is_none() => {
+                self.difficulty = self.difficulty.next();
+                self.show_field = self.difficulty.shows_field();
             },
-            _ => ()
+            KeyCode::F => self.show_field = !self.show_field,
+            KeyCode::T if self.editor.is_none() => self.export_trail(),
+            KeyCode::R if self.editor.is_none() => self.save_replay(),
+            KeyCode::H if self.editor.is_none() => self.show_hint(),
+            _ => self.editor_key(keycode, keymods),
         }
     }
 }
 
+/// Levels played when no `--level` is given, in order.
+const DEFAULT_COURSE: &[&str] = &[
+    include_str!("../levels/01-straight.json"),
+    include_str!("../levels/02-planet.json"),
+    include_str!("../levels/03-binary.json"),
+];
+
+fn level_error(err: gulf::LevelError) -> ggez::GameError {
+    ggez::GameError::ResourceLoadError(err.to_string())
+}
+
 pub fn main() -> ggez::GameResult { 
+    let mut tick_rate = MainState::DEFAULT_TICK_RATE;
+    let mut difficulty = Difficulty::default();
+    let mut integrator = None;
+    let mut level_path = None;
+    let mut replay_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--tick-rate" {
+            tick_rate = args.next()
+                .and_then(|rate| rate.parse().ok())
+                .filter(|&rate| rate > 0)
+                .ok_or_else(|| ggez::GameError::ConfigError(
+                    "--tick-rate expects a positive integer".to_string()
+                ))?;
+        } else if arg == "--difficulty" {
+            difficulty = args.next()
+                .ok_or_else(|| "--difficulty expects easy, normal or hard".to_string())
+                .and_then(|difficulty| difficulty.parse())
+                .map_err(ggez::GameError::ConfigError)?;
+        } else if arg == "--integrator" {
+            integrator = Some(args.next()
+                .ok_or_else(|| "--integrator expects euler, semi_implicit_euler, velocity_verlet or rk4".to_string())
+                .and_then(|integrator| integrator.parse())
+                .map_err(ggez::GameError::ConfigError)?);
+        } else if arg == "--replay" {
+            replay_path = Some(args.next().ok_or_else(|| ggez::GameError::ConfigError(
+                "--replay expects a path".to_string()
+            ))?);
+        } else if arg == "--level" {
+            level_path = Some(args.next().ok_or_else(|| ggez::GameError::ConfigError(
+                "--level expects a path".to_string()
+            ))?);
+        }
+    }
+
+    let mut course: Vec<Level> = match &level_path {
+        Some(path) => vec![gulf::load_level(path).map_err(level_error)?],
+        None => DEFAULT_COURSE.iter()
+            .map(|json| gulf::parse_level(json))
+            .collect::<Result<_, _>>()
+            .map_err(level_error)?,
+    };
+    if let Some(integrator) = integrator {
+        for level in course.iter_mut() {
+            level.integrator = integrator;
+        }
+    }
+    let replay = match replay_path {
+        Some(path) => {
+            let replay = Replay::load(path)
+                .map_err(|err| ggez::GameError::ResourceLoadError(err.to_string()))?;
+            // play back on whichever level the replay was recorded on
+            let level = course.into_iter()
+                .find(|level| gulf::level_hash(level) == replay.level_hash)
+                .ok_or_else(|| ggez::GameError::ConfigError(
+                    "the replay wasn't recorded on any of the given levels".to_string()
+                ))?;
+            course = vec![level];
+            Some(replay)
+        },
+        None => None,
+    };
+
     let cb = ggez::ContextBuilder::new("super_simple", "ggez");
     let (ctx, event_loop) = &mut cb.build()?;
-    let state = &mut MainState::new()?;
+    let save_path = level_path.map(PathBuf::from).unwrap_or_else(|| PathBuf::from("level.json"));
+    let state = &mut MainState::new(ctx, tick_rate, difficulty, course, save_path)?;
+    if let Some(replay) = replay {
+        state.start_playback(replay);
+    }
     event::run(ctx, event_loop, state)
 }
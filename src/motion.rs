@@ -0,0 +1,164 @@
+//! Scripted paths for bodies that move on their own.
+//!
+//! A moving body's position in the level anchors its path: it is where the
+//! body is at the start of the level when its `phase` is 0. Paths are pure
+//! functions of time, so a world can be rewound or replayed exactly.
+
+use std::f32::consts::PI;
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::body::{BigMass, G};
+
+/// How a body moves over the course of a level.
+///
+/// `phase` shifts a path by a fraction of its period, so 0.5 starts the body
+/// half way round. All paths run clockwise on screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Motion {
+    /// Stays where it was placed.
+    #[default]
+    Static,
+    /// Keplerian orbit around the body at index `parent`, with the body's
+    /// position as the periapsis. `period` follows from Kepler's third law
+    /// when left out.
+    Orbit {
+        parent: usize,
+        #[serde(default)]
+        eccentricity: f32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        period: Option<f32>,
+        #[serde(default)]
+        phase: f32,
+    },
+    /// Back and forth at constant speed between the body's position and
+    /// `offset` away from it.
+    PingPong {
+        offset: na::Vector2<f32>,
+        period: f32,
+        #[serde(default)]
+        phase: f32,
+    },
+    /// Circle through the body's position around the point `center` away
+    /// from it.
+    Circle {
+        center: na::Vector2<f32>,
+        period: f32,
+        #[serde(default)]
+        phase: f32,
+    },
+}
+
+impl Motion {
+    pub fn is_static(&self) -> bool {
+        *self == Motion::Static
+    }
+}
+
+/// Where each of `bodies` is `time` seconds into a level, with their `pos`
+/// anchoring their paths.
+pub fn positions_at(bodies: &[BigMass], time: f32) -> Vec<na::Point2<f32>> {
+    let anchors: Vec<_> = bodies.iter().map(|body| body.pos).collect();
+    positions_around(bodies, &anchors, time)
+}
+
+/// Like [`positions_at`], but with bodies without a path at `current`
+/// instead of where they were placed, so orbits follow parents that are
+/// moved some other way.
+pub fn positions_around(
+    bodies: &[BigMass],
+    current: &[na::Point2<f32>],
+    time: f32,
+) -> Vec<na::Point2<f32>> {
+    (0..bodies.len())
+        .map(|i| position_at(bodies, current, i, time, 0))
+        .collect()
+}
+
+/// Position of `bodies[i]`, following orbits up through their parents.
+/// `depth` guards against bodies orbiting each other in a loop, which leaves
+/// them where they were placed.
+fn position_at(
+    bodies: &[BigMass],
+    current: &[na::Point2<f32>],
+    i: usize,
+    time: f32,
+    depth: usize,
+) -> na::Point2<f32> {
+    let body = &bodies[i];
+    match body.motion {
+        Motion::Static => current[i],
+        Motion::Orbit { parent, eccentricity, period, phase } => {
+            let center = match bodies.get(parent) {
+                Some(center) if parent != i && depth < bodies.len() => center,
+                _ => return body.pos,
+            };
+            let periapsis = body.pos - center.pos;
+            let eccentricity = eccentricity.clamp(0.0, 0.99);
+            let semi_major = periapsis.magnitude() / (1.0 - eccentricity);
+            let period = period.unwrap_or_else(|| {
+                // T = 2pi sqrt(a^3 / G(M + m))
+                2.0 * PI * (semi_major.powi(3) / (G * (center.mass + body.mass))).sqrt()
+            });
+            let offset = match periapsis.try_normalize(1e-6) {
+                Some(u) if period > 0.0 => {
+                    let anomaly = eccentric_anomaly(2.0 * PI * (time / period + phase), eccentricity);
+                    let v = na::Vector2::new(-u.y, u.x);
+                    let x = semi_major * (anomaly.cos() - eccentricity);
+                    let y = semi_major * (1.0 - eccentricity.powi(2)).sqrt() * anomaly.sin();
+                    u * x + v * y
+                },
+                _ => periapsis,
+            };
+            position_at(bodies, current, parent, time, depth + 1) + offset
+        },
+        Motion::PingPong { offset, period, phase } => {
+            if period <= 0.0 {
+                return body.pos;
+            }
+            let s = (time / period + phase).rem_euclid(1.0);
+            body.pos + offset * (1.0 - (1.0 - 2.0 * s).abs())
+        },
+        Motion::Circle { center, period, phase } => {
+            if period <= 0.0 {
+                return body.pos;
+            }
+            let rotation = na::Rotation2::new(2.0 * PI * (time / period + phase));
+            let center = body.pos + center;
+            center + rotation * (body.pos - center)
+        },
+    }
+}
+
+/// Keeps orbits pointing at the same parents once `bodies[removed]` has been
+/// taken out of `bodies`. Orbits around the removed body move over to
+/// `replacement`, an index from before the removal, or stop if there is none.
+pub fn remove_parent(bodies: &mut [BigMass], removed: usize, replacement: Option<usize>) {
+    let replacement = replacement.map(|i| if i > removed { i - 1 } else { i });
+    for body in bodies.iter_mut() {
+        if let Motion::Orbit { parent, .. } = &mut body.motion {
+            if *parent == removed {
+                match replacement {
+                    Some(i) => *parent = i,
+                    None => body.motion = Motion::Static,
+                }
+            } else if *parent > removed {
+                *parent -= 1;
+            }
+        }
+    }
+}
+
+/// Solves Kepler's equation `M = E - e sin E` for the eccentric anomaly `E`.
+fn eccentric_anomaly(mean_anomaly: f32, eccentricity: f32) -> f32 {
+    let mean_anomaly = mean_anomaly.rem_euclid(2.0 * PI);
+    // starting from pi converges for any eccentricity below 1
+    let mut anomaly = if eccentricity > 0.8 { PI } else { mean_anomaly };
+    for _ in 0..8 {
+        let error = anomaly - eccentricity * anomaly.sin() - mean_anomaly;
+        anomaly -= error / (1.0 - eccentricity * anomaly.cos());
+    }
+    anomaly
+}
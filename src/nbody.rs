@@ -0,0 +1,106 @@
+//! Bodies pulling on each other, for levels with `n_body` set.
+//!
+//! Bodies without a scripted [`Motion`](crate::motion::Motion) are free and
+//! integrated with velocity Verlet. Bodies on a path pull on the free ones
+//! but keep to their path. The ball is far too light to pull on anything.
+
+use nalgebra as na;
+
+use crate::barnes_hut::QuadTree;
+use crate::body::{BigMass, G};
+use crate::motion;
+
+/// Acceleration of each of `bodies` from the pull of all the others, through
+/// `tree` if there is one for their current positions.
+pub fn accelerations(
+    bodies: &[BigMass],
+    tree: Option<&QuadTree>,
+    theta: f32,
+) -> Vec<na::Vector2<f32>> {
+    let others = |i: usize, j: usize| {
+        if i == j {
+            na::Vector2::zeros()
+        } else {
+            pull(&bodies[i], &bodies[j])
+        }
+    };
+    (0..bodies.len())
+        .map(|i| match tree {
+            Some(tree) => tree.acceleration_at(bodies[i].pos, theta, |j| others(i, j)),
+            None => (0..bodies.len()).fold(na::Vector2::zeros(), |acc, j| acc + others(i, j)),
+        })
+        .collect()
+}
+
+/// Acceleration of `body` towards `other`. The distance is clamped to the
+/// larger radius of the two so that overlapping bodies pull on each other
+/// equally and momentum is conserved.
+fn pull(body: &BigMass, other: &BigMass) -> na::Vector2<f32> {
+    const EPSILON: f32 = 1e-2;
+
+    let offset = other.pos - body.pos;
+    match offset.try_normalize(EPSILON) {
+        Some(dir) => {
+            let distance = offset.magnitude().max(body.radius).max(other.radius);
+            dir * G * other.mass / distance.powi(2)
+        },
+        None => na::Vector2::zeros(),
+    }
+}
+
+/// Merges overlapping bodies until none are left overlapping, conserving
+/// their mass and momentum and keeping their combined area. A body on a path
+/// absorbs a free one and keeps to its path, while two bodies on paths pass
+/// through each other.
+///
+/// `placed` holds the bodies as placed in the level and loses the same
+/// entries as `bodies`. Returns how many merges happened.
+pub fn merge_overlapping(bodies: &mut Vec<BigMass>, placed: &mut Vec<BigMass>) -> usize {
+    let mut merges = 0;
+    while let Some((keep, remove)) = overlapping_pair(bodies) {
+        let other = bodies.remove(remove);
+        placed.remove(remove);
+        motion::remove_parent(bodies, remove, Some(keep));
+        motion::remove_parent(placed, remove, Some(keep));
+
+        let keep = if keep > remove { keep - 1 } else { keep };
+        let body = &mut bodies[keep];
+        let mass = body.mass + other.mass;
+        if body.motion.is_static() {
+            body.pos += (other.pos - body.pos) * (other.mass / mass);
+            body.vel = (body.vel * body.mass + other.vel * other.mass) / mass;
+        }
+        if other.mass > body.mass {
+            body.material = other.material;
+        }
+        body.mass = mass;
+        body.radius = (body.radius.powi(2) + other.radius.powi(2)).sqrt();
+        merges += 1;
+    }
+    merges
+}
+
+/// First pair of overlapping bodies that can merge, as the index of the one
+/// to keep and the one to remove.
+fn overlapping_pair(bodies: &[BigMass]) -> Option<(usize, usize)> {
+    // sweep along x so only bodies that overlap there get compared
+    let left = |i: usize| bodies[i].pos.x - bodies[i].radius;
+    let mut order: Vec<usize> = (0..bodies.len()).collect();
+    order.sort_by(|&a, &b| left(a).total_cmp(&left(b)));
+
+    for (k, &i) in order.iter().enumerate() {
+        let right = bodies[i].pos.x + bodies[i].radius;
+        for &j in order[k + 1..].iter().take_while(|&&j| left(j) < right) {
+            let (a, b) = (i.min(j), i.max(j));
+            if na::distance(&bodies[a].pos, &bodies[b].pos) >= bodies[a].radius + bodies[b].radius {
+                continue;
+            }
+            match (bodies[a].motion.is_static(), bodies[b].motion.is_static()) {
+                (true, true) | (false, true) => return Some((a, b)),
+                (true, false) => return Some((b, a)),
+                (false, false) => (),
+            }
+        }
+    }
+    None
+}
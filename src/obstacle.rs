@@ -0,0 +1,102 @@
+//! Shapes in a level beyond single walls, like bumpers, course boundaries
+//! and maze blocks.
+//!
+//! The ball collides with an obstacle through the [`Wall`]s along its edges,
+//! so obstacles bounce the ball exactly like walls do, and give it a kick
+//! when their material's restitution is above 1.
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::body::Material;
+use crate::wall::Wall;
+
+/// Outline of an obstacle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Shape {
+    /// Closed outline through `points` in order, back to the first.
+    Polygon { points: Vec<na::Point2<f32>> },
+    /// Rectangle of `size` around `center`, turned by `angle` radians.
+    Rectangle {
+        center: na::Point2<f32>,
+        size: na::Vector2<f32>,
+        #[serde(default)]
+        angle: f32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Obstacle {
+    #[serde(flatten)]
+    pub shape: Shape,
+    #[serde(default)]
+    pub material: Material,
+    /// Only the outline is there, like a course boundary the ball plays
+    /// inside of, rather than a solid block.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hollow: bool,
+}
+
+impl Obstacle {
+    pub fn new(shape: Shape) -> Obstacle {
+        Obstacle {
+            shape,
+            material: Material::default(),
+            hollow: false,
+        }
+    }
+
+    pub fn with_material(mut self, material: Material) -> Obstacle {
+        self.material = material;
+        self
+    }
+
+    /// Corners of the outline in order.
+    pub fn points(&self) -> Vec<na::Point2<f32>> {
+        match &self.shape {
+            Shape::Polygon { points } => points.clone(),
+            Shape::Rectangle { center, size, angle } => {
+                let rotation = na::Rotation2::new(*angle);
+                let half = size / 2.0;
+                [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].iter()
+                    .map(|&(x, y)| center + rotation * na::Vector2::new(half.x * x, half.y * y))
+                    .collect()
+            },
+        }
+    }
+
+    /// Whether the outline has enough corners to enclose anything.
+    pub fn is_closed(&self) -> bool {
+        self.points().len() >= 3
+    }
+
+    /// Walls along every edge of the outline, none if it isn't closed.
+    pub fn walls(&self) -> Vec<Wall> {
+        if !self.is_closed() {
+            return vec![];
+        }
+        let points = self.points();
+        let n = points.len();
+        (0..n)
+            .map(|i| Wall { a: points[i], b: points[(i + 1) % n], material: self.material })
+            .collect()
+    }
+
+    /// Whether `pos` is inside the outline, by the even-odd rule.
+    pub fn contains(&self, pos: na::Point2<f32>) -> bool {
+        let points = self.points();
+        let n = points.len();
+        let mut inside = false;
+        for i in 0..n {
+            let (a, b) = (points[i], points[(i + n - 1) % n]);
+            if (a.y > pos.y) != (b.y > pos.y) {
+                let x = a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if pos.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
@@ -0,0 +1,183 @@
+//! Recording shots so a play-through can be simulated again exactly.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::level::Level;
+use crate::shot::Shot;
+use crate::world::World;
+
+/// A shot and the tick it was taken on, before that tick was stepped.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShotRecord {
+    pub tick: u64,
+    #[serde(flatten)]
+    pub shot: Shot,
+}
+
+/// Everything needed to play a level again and check it ends the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// [`level_hash`] of the level that was played.
+    pub level_hash: u64,
+    /// The level's seed at the time of recording.
+    pub seed: u64,
+    /// Ticks per second the world was stepped at.
+    pub tick_rate: u32,
+    /// Shots in the order they were taken.
+    pub shots: Vec<ShotRecord>,
+    /// Tick the recording ended on.
+    pub final_tick: u64,
+    /// Where the ball was when the recording ended.
+    pub final_pos: na::Point2<f32>,
+}
+
+impl Replay {
+    pub fn new(level: &Level, tick_rate: u32) -> Replay {
+        Replay {
+            level_hash: level_hash(level),
+            seed: level.seed,
+            tick_rate,
+            shots: vec![],
+            final_tick: 0,
+            final_pos: level.ball_start,
+        }
+    }
+
+    pub fn record(&mut self, tick: u64, shot: Shot) {
+        self.shots.push(ShotRecord { tick, shot });
+    }
+
+    /// Marks the current state of `world` as the end of the recording.
+    pub fn finish(&mut self, world: &World) {
+        self.final_tick = world.tick();
+        self.final_pos = world.ball.pos;
+    }
+
+    pub fn dt(&self) -> f32 {
+        1.0 / self.tick_rate as f32
+    }
+
+    /// Shots recorded for `tick`, to be taken before stepping it.
+    pub fn shots_at(&self, tick: u64) -> impl Iterator<Item = &Shot> {
+        self.shots.iter()
+            .filter(move |record| record.tick == tick)
+            .map(|record| &record.shot)
+    }
+
+    /// Takes the shots due on the current tick of `world`, then steps it.
+    pub fn step(&self, world: &mut World) {
+        for shot in self.shots_at(world.tick()) {
+            world.shoot(shot.force());
+        }
+        world.step(self.dt());
+    }
+
+    /// Whether `world` has been stepped up to the end of the recording.
+    pub fn is_done(&self, world: &World) -> bool {
+        world.tick() >= self.final_tick
+    }
+
+    /// Simulates `level` with the recorded shots and checks the ball ends up
+    /// exactly where it did when recording.
+    pub fn verify(&self, level: &Level) -> Result<World, ReplayError> {
+        let found = level_hash(level);
+        if found != self.level_hash {
+            return Err(ReplayError::LevelMismatch { expected: self.level_hash, found });
+        }
+
+        let mut world = level.world();
+        while !self.is_done(&world) {
+            self.step(&mut world);
+        }
+        self.check(&world)?;
+        Ok(world)
+    }
+
+    /// Checks a world played back to the end matches the recording.
+    pub fn check(&self, world: &World) -> Result<(), ReplayError> {
+        if world.ball.pos == self.final_pos {
+            Ok(())
+        } else {
+            Err(ReplayError::Diverged { expected: self.final_pos, found: world.ball.pos })
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Replay, ReplayError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReplayError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Stable 64-bit FNV-1a hash of a level's serialized form, used to tell
+/// whether a replay belongs to it.
+pub fn level_hash(level: &Level) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let json = serde_json::to_string(level).expect("levels always serialize");
+    json.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    /// The replay was recorded on a different level.
+    LevelMismatch { expected: u64, found: u64 },
+    /// Playing the replay back didn't end where the recording did.
+    Diverged { expected: na::Point2<f32>, found: na::Point2<f32> },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "couldn't access replay file: {}", err),
+            ReplayError::Format(err) => write!(f, "invalid replay: {}", err),
+            ReplayError::LevelMismatch { expected, found } => write!(
+                f,
+                "replay was recorded on level {:016x} but this is level {:016x}",
+                expected, found
+            ),
+            ReplayError::Diverged { expected, found } => write!(
+                f,
+                "replay ended at ({}, {}) but was recorded ending at ({}, {})",
+                found.x, found.y, expected.x, expected.y
+            ),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::Io(err) => Some(err),
+            ReplayError::Format(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> ReplayError {
+        ReplayError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ReplayError {
+    fn from(err: serde_json::Error) -> ReplayError {
+        ReplayError::Format(err)
+    }
+}
@@ -0,0 +1,102 @@
+//! Per-level rules for when a shot goes wrong.
+//!
+//! A shot that breaks a rule costs `penalty` strokes on top of the stroke
+//! itself, and the ball goes back to where it was shot from.
+
+use std::fmt;
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{Collider, Collision};
+
+/// Rectangle the ball has to stay inside of.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub min: na::Point2<f32>,
+    pub max: na::Point2<f32>,
+}
+
+impl Bounds {
+    pub fn contains(&self, pos: na::Point2<f32>) -> bool {
+        pos.x >= self.min.x && pos.x <= self.max.x && pos.y >= self.min.y && pos.y <= self.max.y
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rules {
+    /// Where the ball is in play, everywhere when left out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<Bounds>,
+    /// Longest the ball may be in flight after a shot, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_limit: Option<f32>,
+    /// Speed at which hitting a body crashes into it, 0 for any contact at
+    /// all. Bodies can be hit at any speed when left out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crash_speed: Option<f32>,
+    /// Strokes added for breaking a rule.
+    #[serde(default = "default_penalty")]
+    pub penalty: u32,
+}
+
+fn default_penalty() -> u32 {
+    1
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            bounds: None,
+            time_limit: None,
+            crash_speed: None,
+            penalty: default_penalty(),
+        }
+    }
+}
+
+impl Rules {
+    pub fn is_default(&self) -> bool {
+        *self == Rules::default()
+    }
+
+    /// The rule broken by a ball at `pos` that has been in flight for
+    /// `flight_time` seconds and just made `collisions`, if any.
+    pub fn fault(&self, pos: na::Point2<f32>, flight_time: f32, collisions: &[Collision]) -> Option<Fault> {
+        if let Some(crash_speed) = self.crash_speed {
+            let crash = collisions.iter().find(|collision| {
+                matches!(collision.collider, Collider::Body(_)) && collision.speed >= crash_speed
+            });
+            if let Some(&Collision { collider: Collider::Body(body), .. }) = crash {
+                return Some(Fault::Crash { body });
+            }
+        }
+        if self.bounds.is_some_and(|bounds| !bounds.contains(pos)) {
+            return Some(Fault::OutOfBounds);
+        }
+        if self.time_limit.is_some_and(|limit| flight_time > limit) {
+            return Some(Fault::TimeLimit);
+        }
+        None
+    }
+}
+
+/// A broken rule that sent the ball back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fault {
+    OutOfBounds,
+    TimeLimit,
+    /// The ball crashed into the body with this index.
+    Crash { body: usize },
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fault::OutOfBounds => write!(f, "Out of bounds"),
+            Fault::TimeLimit => write!(f, "Out of time"),
+            Fault::Crash { .. } => write!(f, "Crashed"),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Strokes taken on a single hole against its par.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HoleScore {
+    pub par: u32,
+    pub strokes: u32,
+}
+
+impl HoleScore {
+    /// Strokes over par, negative when under.
+    pub fn to_par(&self) -> i32 {
+        self.strokes as i32 - self.par as i32
+    }
+
+    pub fn label(&self) -> ScoreLabel {
+        if self.strokes == 1 {
+            return ScoreLabel::HoleInOne;
+        }
+        match self.to_par() {
+            -3 => ScoreLabel::Albatross,
+            -2 => ScoreLabel::Eagle,
+            -1 => ScoreLabel::Birdie,
+            0 => ScoreLabel::Par,
+            1 => ScoreLabel::Bogey,
+            2 => ScoreLabel::DoubleBogey,
+            3 => ScoreLabel::TripleBogey,
+            diff if diff < 0 => ScoreLabel::Under(-diff as u32),
+            diff => ScoreLabel::Over(diff as u32),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreLabel {
+    HoleInOne,
+    Albatross,
+    Eagle,
+    Birdie,
+    Par,
+    Bogey,
+    DoubleBogey,
+    TripleBogey,
+    /// Further under par than has a name.
+    Under(u32),
+    /// Further over par than has a name.
+    Over(u32),
+}
+
+impl fmt::Display for ScoreLabel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScoreLabel::HoleInOne => write!(f, "Hole in one"),
+            ScoreLabel::Albatross => write!(f, "Albatross"),
+            ScoreLabel::Eagle => write!(f, "Eagle"),
+            ScoreLabel::Birdie => write!(f, "Birdie"),
+            ScoreLabel::Par => write!(f, "Par"),
+            ScoreLabel::Bogey => write!(f, "Bogey"),
+            ScoreLabel::DoubleBogey => write!(f, "Double bogey"),
+            ScoreLabel::TripleBogey => write!(f, "Triple bogey"),
+            ScoreLabel::Under(n) => write!(f, "{} under par", n),
+            ScoreLabel::Over(n) => write!(f, "{} over par", n),
+        }
+    }
+}
+
+/// Scores of every hole played so far on a course, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scorecard {
+    pub holes: Vec<HoleScore>,
+}
+
+impl Scorecard {
+    pub fn new() -> Scorecard {
+        Scorecard::default()
+    }
+
+    pub fn record(&mut self, par: u32, strokes: u32) -> HoleScore {
+        let score = HoleScore { par, strokes };
+        self.holes.push(score);
+        score
+    }
+
+    pub fn total_strokes(&self) -> u32 {
+        self.holes.iter().map(|hole| hole.strokes).sum()
+    }
+
+    pub fn total_par(&self) -> u32 {
+        self.holes.iter().map(|hole| hole.par).sum()
+    }
+
+    /// Strokes over par for the whole card, negative when under.
+    pub fn to_par(&self) -> i32 {
+        self.holes.iter().map(HoleScore::to_par).sum()
+    }
+}
@@ -0,0 +1,42 @@
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+/// A hit of the ball: which way and how hard.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Shot {
+    /// Unit vector the ball is sent along.
+    pub aim: na::Vector2<f32>,
+    /// Magnitude of the force applied to the ball.
+    pub power: f32,
+}
+
+impl Shot {
+    pub fn new(aim: na::Vector2<f32>, power: f32) -> Shot {
+        const EPSILON: f32 = 1e-6;
+
+        Shot {
+            aim: aim.try_normalize(EPSILON).unwrap_or_else(na::Vector2::x),
+            power,
+        }
+    }
+
+    /// A shot at `angle` radians from the x axis, towards positive y.
+    pub fn from_angle(angle: f32, power: f32) -> Shot {
+        Shot {
+            aim: na::Vector2::new(angle.cos(), angle.sin()),
+            power,
+        }
+    }
+
+    pub fn from_force(force: na::Vector2<f32>) -> Shot {
+        Shot::new(force, force.magnitude())
+    }
+
+    pub fn angle(&self) -> f32 {
+        self.aim.y.atan2(self.aim.x)
+    }
+
+    pub fn force(&self) -> na::Vector2<f32> {
+        self.aim * self.power
+    }
+}
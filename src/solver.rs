@@ -0,0 +1,237 @@
+//! Searching for shots that sink the ball.
+//!
+//! Shots are evaluated by simulating them on a clone of the world until the
+//! ball settles, and scored by how far from the hole it ends up. A coarse grid
+//! over angle and power finds promising shots, which are then refined with a
+//! pattern search. Strokes are chained with a small beam search, keeping the
+//! few best positions after each stroke.
+
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use nalgebra as na;
+
+use crate::level::Level;
+use crate::shot::Shot;
+use crate::world::World;
+
+#[derive(Debug, Clone)]
+pub struct SolverConfig {
+    /// Ticks per second to simulate at, should match the game.
+    pub tick_rate: u32,
+    /// Longest a shot is simulated for before giving up on it settling.
+    pub max_ticks: u64,
+    pub max_strokes: u32,
+    pub min_power: f32,
+    pub max_power: f32,
+    /// Number of angles tried on the coarse grid.
+    pub angle_steps: usize,
+    /// Number of powers tried on the coarse grid.
+    pub power_steps: usize,
+    /// Grid shots that are refined further.
+    pub refine_candidates: usize,
+    /// Pattern search iterations per refined shot.
+    pub refine_iterations: usize,
+    /// Positions kept after each stroke when chaining shots.
+    pub beam_width: usize,
+    /// Set from another thread to stop a search early, which then returns
+    /// whatever it found so far.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for SolverConfig {
+    fn default() -> SolverConfig {
+        SolverConfig {
+            tick_rate: 60,
+            max_ticks: 60 * 30,
+            max_strokes: 6,
+            min_power: 50.0,
+            max_power: 5000.0,
+            angle_steps: 72,
+            power_steps: 16,
+            refine_candidates: 3,
+            refine_iterations: 24,
+            beam_width: 3,
+            cancel: None,
+        }
+    }
+}
+
+impl SolverConfig {
+    fn dt(&self) -> f32 {
+        1.0 / self.tick_rate as f32
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+}
+
+/// A shot and the world it leaves behind once the ball settles.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub shot: Shot,
+    pub world: World,
+    /// Distance from the ball to the hole once it settled, 0 when sunk.
+    pub distance: f32,
+}
+
+impl Outcome {
+    pub fn is_sunk(&self) -> bool {
+        self.world.is_sunk()
+    }
+}
+
+/// Shots found for a level, in order.
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub shots: Vec<Shot>,
+    /// Whether the last shot sinks the ball.
+    pub holed: bool,
+    /// Distance from the ball to the hole after the last shot.
+    pub distance: f32,
+    /// Penalty strokes the shots took for breaking the level's rules.
+    pub penalties: u32,
+}
+
+impl Solution {
+    /// Shots plus penalty strokes.
+    pub fn strokes(&self) -> u32 {
+        self.shots.len() as u32 + self.penalties
+    }
+}
+
+/// Simulates `shot` from `world` until the ball settles.
+pub fn simulate(world: &World, shot: Shot, config: &SolverConfig) -> Outcome {
+    let mut world = world.clone();
+    world.shoot(shot.force());
+    world.settle(config.dt(), config.max_ticks);
+    let distance = if world.is_sunk() {
+        0.0
+    } else {
+        na::distance(&world.ball.pos, &world.hole.pos)
+    };
+    // a shot whose simulation blew up is as bad as it gets
+    let distance = if distance.is_nan() { f32::INFINITY } else { distance };
+    Outcome { shot, world, distance }
+}
+
+fn by_distance(a: &Outcome, b: &Outcome) -> std::cmp::Ordering {
+    a.distance.total_cmp(&b.distance)
+}
+
+/// Evaluates every shot of the coarse grid, closest to the hole first.
+fn grid_search(world: &World, config: &SolverConfig) -> Vec<Outcome> {
+    let power_step = if config.power_steps > 1 {
+        (config.max_power - config.min_power) / (config.power_steps - 1) as f32
+    } else {
+        0.0
+    };
+    let mut outcomes = Vec::with_capacity(config.angle_steps * config.power_steps);
+    for a in 0..config.angle_steps {
+        let angle = 2.0 * PI * a as f32 / config.angle_steps as f32;
+        if config.is_cancelled() {
+            break;
+        }
+        for p in 0..config.power_steps {
+            let power = config.min_power + power_step * p as f32;
+            outcomes.push(simulate(world, Shot::from_angle(angle, power), config));
+        }
+    }
+    outcomes.sort_by(by_distance);
+    outcomes
+}
+
+/// Improves on `start` by probing nearby angles and powers, shrinking the
+/// probe whenever none of them is any closer.
+fn refine(world: &World, start: Outcome, config: &SolverConfig) -> Outcome {
+    let mut best = start;
+    let mut angle_step = PI / config.angle_steps as f32;
+    let mut power_step = (config.max_power - config.min_power) / (2 * config.power_steps) as f32;
+    for _ in 0..config.refine_iterations {
+        if best.is_sunk() || config.is_cancelled() {
+            break;
+        }
+
+        let angle = best.shot.angle();
+        let power = best.shot.power;
+        let probes = [
+            (angle + angle_step, power),
+            (angle - angle_step, power),
+            (angle, power + power_step),
+            (angle, power - power_step),
+        ];
+        let improved = probes.iter()
+            .map(|&(angle, power)| {
+                let power = power.clamp(config.min_power, config.max_power);
+                simulate(world, Shot::from_angle(angle, power), config)
+            })
+            .filter(|outcome| outcome.distance < best.distance)
+            .min_by(by_distance);
+        match improved {
+            Some(outcome) => best = outcome,
+            None => {
+                angle_step /= 2.0;
+                power_step /= 2.0;
+            },
+        }
+    }
+    best
+}
+
+/// The best few single shots from `world`, closest to the hole first.
+pub fn best_shots(world: &World, config: &SolverConfig) -> Vec<Outcome> {
+    let mut grid = grid_search(world, config);
+    if grid.first().is_some_and(Outcome::is_sunk) {
+        grid.retain(Outcome::is_sunk);
+        grid.truncate(config.refine_candidates.max(1));
+        return grid;
+    }
+
+    grid.truncate(config.refine_candidates.max(1));
+    let mut refined: Vec<_> = grid.into_iter()
+        .map(|outcome| refine(world, outcome, config))
+        .collect();
+    refined.sort_by(by_distance);
+    refined
+}
+
+/// The single shot from `world` that gets the ball closest to the hole.
+pub fn best_shot(world: &World, config: &SolverConfig) -> Option<Outcome> {
+    best_shots(world, config).into_iter().next()
+}
+
+/// Searches for the fewest shots that sink the ball from where it is in
+/// `world`. When none is found within `max_strokes`, returns the sequence
+/// that got closest.
+pub fn solve_from(world: &World, config: &SolverConfig) -> Solution {
+    let mut beam = vec![(vec![], world.clone(), na::distance(&world.ball.pos, &world.hole.pos))];
+    for _ in 0..config.max_strokes {
+        let mut next = vec![];
+        for (shots, world, _) in beam.iter() {
+            for outcome in best_shots(world, config) {
+                let mut shots = shots.clone();
+                shots.push(outcome.shot);
+                if outcome.is_sunk() {
+                    let penalties = outcome.world.penalties() - world.penalties();
+                    return Solution { shots, holed: true, distance: 0.0, penalties };
+                }
+                next.push((shots, outcome.world, outcome.distance));
+            }
+        }
+        next.sort_by(|a, b| a.2.total_cmp(&b.2));
+        next.truncate(config.beam_width.max(1));
+        beam = next;
+    }
+
+    let (shots, end, distance) = beam.into_iter().next().unwrap_or((vec![], world.clone(), f32::INFINITY));
+    let penalties = end.penalties() - world.penalties();
+    Solution { shots, holed: false, distance, penalties }
+}
+
+/// Searches for the fewest shots that sink the ball from the start of
+/// `level`.
+pub fn solve(level: &Level, config: &SolverConfig) -> Solution {
+    solve_from(&level.world(), config)
+}
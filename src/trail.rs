@@ -0,0 +1,73 @@
+use std::collections::VecDeque;
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+/// Where the ball was at some moment of the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TrailPoint {
+    /// Simulated seconds since the world was created.
+    pub time: f32,
+    pub pos: na::Point2<f32>,
+    pub speed: f32,
+}
+
+/// Recent motion history of the ball, oldest point first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trail {
+    /// Seconds of history kept.
+    pub duration: f32,
+    points: VecDeque<TrailPoint>,
+}
+
+impl Trail {
+    pub const DEFAULT_DURATION: f32 = 3.0;
+
+    pub fn new(duration: f32) -> Trail {
+        Trail {
+            duration,
+            points: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, point: TrailPoint) {
+        self.points.push_back(point);
+        self.prune(point.time);
+    }
+
+    /// Forgets points older than `duration` at `now`.
+    pub fn prune(&mut self, now: f32) {
+        while let Some(oldest) = self.points.front() {
+            if now - oldest.time <= self.duration {
+                break;
+            }
+            self.points.pop_front();
+        }
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &TrailPoint> {
+        self.points.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl Default for Trail {
+    fn default() -> Trail {
+        Trail::new(Self::DEFAULT_DURATION)
+    }
+}
@@ -0,0 +1,118 @@
+//! Checking that levels are well formed and can be finished within par.
+
+use nalgebra as na;
+use serde::Serialize;
+
+use crate::level::Level;
+use crate::motion;
+use crate::solver::{self, SolverConfig};
+use crate::world::Ball;
+
+/// Something wrong with a level.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    /// The ball starts overlapping a body.
+    BallInsideBody { body: usize },
+    /// The ball starts overlapping a wall.
+    BallInsideWall { wall: usize },
+    /// An obstacle has fewer than 3 corners, so the ball can't hit it.
+    OpenObstacle { obstacle: usize },
+    /// The ball starts inside a solid obstacle.
+    BallInsideObstacle { obstacle: usize },
+    /// The hole lies inside a solid obstacle.
+    HoleInsideObstacle { obstacle: usize },
+    /// The ball starts outside the level's bounds.
+    BallOutOfBounds,
+    /// The hole lies outside the level's bounds.
+    HoleOutOfBounds,
+    /// The hole lies under a body.
+    HoleInsideBody { body: usize },
+    /// The solver couldn't sink the ball at all.
+    Unreachable { closest: f32 },
+    /// The solver only sank the ball in more strokes than par.
+    OverPar { strokes: u32, par: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub par: u32,
+    /// Fewest strokes the solver sank the ball in, if it did.
+    pub min_strokes: Option<u32>,
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Problems with where things are placed in `level`, without simulating it.
+pub fn placement_issues(level: &Level) -> Vec<Issue> {
+    let mut issues = vec![];
+    let positions = motion::positions_at(&level.bodies, 0.0);
+    for (i, (body, pos)) in level.bodies.iter().zip(positions).enumerate() {
+        if na::distance(&level.ball_start, &pos) < body.radius + Ball::RADIUS {
+            issues.push(Issue::BallInsideBody { body: i });
+        }
+        if na::distance(&level.hole.pos, &pos) < body.radius {
+            issues.push(Issue::HoleInsideBody { body: i });
+        }
+    }
+    for (i, wall) in level.walls.iter().enumerate() {
+        if na::distance(&level.ball_start, &wall.closest_point(level.ball_start)) < Ball::RADIUS {
+            issues.push(Issue::BallInsideWall { wall: i });
+        }
+    }
+    for (i, obstacle) in level.obstacles.iter().enumerate() {
+        if !obstacle.is_closed() {
+            issues.push(Issue::OpenObstacle { obstacle: i });
+            continue;
+        }
+        let touching = obstacle.walls().iter()
+            .any(|wall| na::distance(&level.ball_start, &wall.closest_point(level.ball_start)) < Ball::RADIUS);
+        let solid = !obstacle.hollow;
+        if touching || (solid && obstacle.contains(level.ball_start)) {
+            issues.push(Issue::BallInsideObstacle { obstacle: i });
+        }
+        if solid && obstacle.contains(level.hole.pos) {
+            issues.push(Issue::HoleInsideObstacle { obstacle: i });
+        }
+    }
+    if let Some(bounds) = level.rules.bounds {
+        if !bounds.contains(level.ball_start) {
+            issues.push(Issue::BallOutOfBounds);
+        }
+        if !bounds.contains(level.hole.pos) {
+            issues.push(Issue::HoleOutOfBounds);
+        }
+    }
+    issues
+}
+
+/// Checks the placement in `level` and has the solver play it to find the
+/// fewest strokes it can be finished in.
+pub fn verify_level(level: &Level, config: &SolverConfig) -> Report {
+    let mut issues = placement_issues(level);
+
+    let solution = solver::solve(level, config);
+    let min_strokes = if solution.holed {
+        Some(solution.strokes())
+    } else {
+        None
+    };
+    match min_strokes {
+        Some(strokes) if strokes > level.par => {
+            issues.push(Issue::OverPar { strokes, par: level.par });
+        },
+        Some(_) => (),
+        None => issues.push(Issue::Unreachable { closest: solution.distance }),
+    }
+
+    Report {
+        par: level.par,
+        min_strokes,
+        issues,
+    }
+}
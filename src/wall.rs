@@ -0,0 +1,34 @@
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::body::Material;
+
+/// A straight, static piece of level geometry between `a` and `b`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Wall {
+    pub a: na::Point2<f32>,
+    pub b: na::Point2<f32>,
+    #[serde(default)]
+    pub material: Material,
+}
+
+impl Wall {
+    pub fn new(a: na::Point2<f32>, b: na::Point2<f32>) -> Wall {
+        Wall {
+            a,
+            b,
+            material: Material::default(),
+        }
+    }
+
+    /// Point of the wall closest to `pos`.
+    pub fn closest_point(&self, pos: na::Point2<f32>) -> na::Point2<f32> {
+        let along = self.b - self.a;
+        let len_sq = along.magnitude_squared();
+        if len_sq == 0.0 {
+            return self.a;
+        }
+        let s = ((pos - self.a).dot(&along) / len_sq).clamp(0.0, 1.0);
+        self.a + along * s
+    }
+}
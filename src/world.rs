@@ -0,0 +1,546 @@
+use nalgebra as na;
+
+use crate::barnes_hut::{BarnesHut, QuadTree};
+use crate::body::{BigMass, Material};
+use crate::collision::{self, Collider, Collision, Impact};
+use crate::drag::{self, Drag};
+use crate::field;
+use crate::hole::Hole;
+use crate::integrator::Method;
+use crate::motion;
+use crate::nbody;
+use crate::obstacle::Obstacle;
+use crate::rules::{Fault, Rules};
+use crate::trail::{Trail, TrailPoint};
+use crate::wall::Wall;
+
+#[derive(Debug, Clone)]
+pub struct Ball {
+    pub pos: na::Point2<f32>,
+    pub vel: na::Vector2<f32>,
+    pub acc: na::Vector2<f32>,
+    resting: bool,
+}
+
+impl Ball {
+    pub const MASS: f32 = 2.0;
+    pub const RADIUS: f32 = 10.0;
+    /// Speed in units per second below which the ball can come to rest.
+    pub const REST_SPEED: f32 = 0.6;
+    /// How much the ball's velocity may still be changing, in units per
+    /// second squared, for it to come to rest. Keeps a ball that is only
+    /// slow at the top of its arc, or while gravity is still pulling it
+    /// along, from stopping in mid air.
+    pub const REST_ACCELERATION: f32 = 10.0;
+
+    /// A ball at rest on `pos`.
+    pub fn new(pos: na::Point2<f32>) -> Ball {
+        Ball {
+            pos,
+            vel: na::Vector2::zeros(),
+            acc: na::Vector2::zeros(),
+            resting: true,
+        }
+    }
+
+    pub fn is_moving(&self) -> bool {
+        !self.resting
+    }
+
+    /// Stops the ball if it is slow and its velocity is barely changing any
+    /// more, having gone from `prev_vel` to its current one over `dt`.
+    fn settle(&mut self, prev_vel: na::Vector2<f32>, dt: f32) {
+        let measured_acc = (self.vel - prev_vel) / dt;
+        if self.vel.magnitude() < Self::REST_SPEED && measured_acc.magnitude() < Self::REST_ACCELERATION {
+            self.vel = na::Vector2::zeros();
+            self.resting = true;
+        }
+    }
+}
+
+/// The whole simulated state: the ball, the bodies pulling on it, the walls
+/// in its way and the hole it is aimed at.
+///
+/// A `World` only changes through [`World::shoot`] and [`World::step`], so a
+/// clone can be stepped independently of the original.
+#[derive(Debug, Clone)]
+pub struct World {
+    pub ball: Ball,
+    bodies: Vec<BigMass>,
+    /// Bodies as placed in the level, anchoring the paths of moving ones.
+    placed: Vec<BigMass>,
+    pub walls: Vec<Wall>,
+    obstacles: Vec<Obstacle>,
+    /// Walls along the edges of each of `obstacles`, which are what the
+    /// ball actually runs into.
+    obstacle_edges: Vec<Vec<Wall>>,
+    pub hole: Hole,
+    /// How the ball is moved through the gravity field each step.
+    pub integrator: Method,
+    /// Drag slowing the ball everywhere, on top of any atmospheres.
+    pub drag: Drag,
+    /// Whether free bodies pull on each other and move.
+    pub n_body: bool,
+    /// Whether bodies that run into each other merge, with `n_body` set.
+    pub merge_bodies: bool,
+    /// When gravity is approximated with a quadtree.
+    barnes_hut: BarnesHut,
+    /// Quadtree over the bodies where they are now, if there are enough of
+    /// them for `barnes_hut` to use one. Rebuilt whenever either changes,
+    /// through [`World::set_bodies`], [`World::set_barnes_hut`] or
+    /// [`World::step`].
+    tree: Option<QuadTree>,
+    /// Accelerations of the bodies where they are now, left from the last
+    /// N-body step for the next one to start from. Empty when out of date.
+    body_acc: Vec<na::Vector2<f32>>,
+    /// Where the ball has been over the last few seconds.
+    pub trail: Trail,
+    /// What the ball hit during the last step.
+    pub collisions: Vec<Collision>,
+    /// What costs penalty strokes and sends the ball back.
+    pub rules: Rules,
+    /// The rule the ball broke during the last step, if it was sent back.
+    pub fault: Option<Fault>,
+    /// Penalty strokes taken since the world was created.
+    penalties: u32,
+    /// Where the ball last lay at rest before its current flight.
+    rest_pos: na::Point2<f32>,
+    /// [`World::time`] the ball's current flight started at.
+    flight_start: f32,
+    /// Steps taken since the world was created.
+    tick: u64,
+    /// Simulated seconds since the world was created.
+    time: f32,
+    sunk: bool,
+}
+
+impl World {
+    /// Hits of the ball bounced off per step. Only a ball wedged in a tight
+    /// corner hits more, and then loses whatever motion of the step is left
+    /// past the next surface in its way.
+    pub const MAX_IMPACTS: usize = 8;
+
+    pub fn new(ball_start: na::Point2<f32>, hole: Hole) -> World {
+        World {
+            ball: Ball::new(ball_start),
+            bodies: vec![],
+            placed: vec![],
+            walls: vec![],
+            obstacles: vec![],
+            obstacle_edges: vec![],
+            hole,
+            integrator: Method::default(),
+            drag: Drag::default(),
+            n_body: false,
+            merge_bodies: false,
+            barnes_hut: BarnesHut::default(),
+            tree: None,
+            body_acc: vec![],
+            trail: Trail::default(),
+            collisions: vec![],
+            rules: Rules::default(),
+            fault: None,
+            penalties: 0,
+            rest_pos: ball_start,
+            flight_start: 0.0,
+            tick: 0,
+            time: 0.0,
+            sunk: false,
+        }
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Penalty strokes taken for breaking the rules.
+    pub fn penalties(&self) -> u32 {
+        self.penalties
+    }
+
+    /// Whether the ball has been captured by the hole.
+    pub fn is_sunk(&self) -> bool {
+        self.sunk
+    }
+
+    pub fn obstacles(&self) -> &[Obstacle] {
+        &self.obstacles
+    }
+
+    /// Walls along the edges of every obstacle.
+    pub fn obstacle_edges(&self) -> impl Iterator<Item = &Wall> {
+        self.obstacle_edges.iter().flatten()
+    }
+
+    /// Replaces the obstacles with `obstacles`.
+    pub fn set_obstacles(&mut self, obstacles: Vec<Obstacle>) {
+        self.obstacle_edges = obstacles.iter().map(Obstacle::walls).collect();
+        self.obstacles = obstacles;
+    }
+
+    /// The bodies where they are now.
+    pub fn bodies(&self) -> &[BigMass] {
+        &self.bodies
+    }
+
+    pub fn barnes_hut(&self) -> BarnesHut {
+        self.barnes_hut
+    }
+
+    /// Switches to approximating gravity as `barnes_hut` says, rebuilding
+    /// the quadtree over the bodies to match.
+    pub fn set_barnes_hut(&mut self, barnes_hut: BarnesHut) {
+        self.barnes_hut = barnes_hut;
+        self.tree = self.barnes_hut.tree(&self.bodies);
+        self.body_acc.clear();
+    }
+
+    /// Replaces the bodies with `bodies` as placed in a level, moving those
+    /// with a path to where they are at the current time. Only free bodies
+    /// in an N-body world keep their velocity, so set `n_body` first.
+    pub fn set_bodies(&mut self, bodies: Vec<BigMass>) {
+        self.bodies = bodies.clone();
+        self.placed = bodies;
+        let positions = motion::positions_at(&self.placed, self.time);
+        for (body, pos) in self.bodies.iter_mut().zip(positions) {
+            body.pos = pos;
+            if !(self.n_body && body.motion.is_static()) {
+                body.vel = na::Vector2::zeros();
+            }
+        }
+        self.tree = self.barnes_hut.tree(&self.bodies);
+        self.body_acc.clear();
+    }
+
+    /// Moves bodies along their paths to the current time, `dt` after their
+    /// last positions, and integrates free bodies with velocity Verlet in an
+    /// N-body world.
+    fn move_bodies(&mut self, dt: f32) {
+        let scripted = self.placed.iter().any(|body| !body.motion.is_static());
+        if !scripted && !self.n_body {
+            return;
+        }
+
+        let acc = if self.n_body {
+            let acc = if self.body_acc.len() == self.bodies.len() {
+                std::mem::take(&mut self.body_acc)
+            } else {
+                nbody::accelerations(&self.bodies, self.tree.as_ref(), self.barnes_hut.theta)
+            };
+            for (body, acc) in self.bodies.iter_mut().zip(acc.iter()) {
+                if body.motion.is_static() {
+                    body.pos += body.vel * dt + acc * (0.5 * dt * dt);
+                }
+            }
+            acc
+        } else {
+            vec![]
+        };
+
+        if scripted {
+            let current: Vec<_> = self.bodies.iter().map(|body| body.pos).collect();
+            let positions = motion::positions_around(&self.placed, &current, self.time);
+            for (body, pos) in self.bodies.iter_mut().zip(positions) {
+                if !body.motion.is_static() {
+                    body.vel = (pos - body.pos) / dt;
+                    body.pos = pos;
+                }
+            }
+        }
+
+        self.tree = self.barnes_hut.tree(&self.bodies);
+        if self.n_body {
+            let new_acc = nbody::accelerations(&self.bodies, self.tree.as_ref(), self.barnes_hut.theta);
+            for (body, (acc, new_acc)) in self.bodies.iter_mut().zip(acc.iter().zip(new_acc.iter())) {
+                if body.motion.is_static() {
+                    body.vel += (acc + new_acc) * (0.5 * dt);
+                }
+            }
+            self.body_acc = new_acc;
+            if self.merge_bodies && nbody::merge_overlapping(&mut self.bodies, &mut self.placed) > 0 {
+                self.tree = self.barnes_hut.tree(&self.bodies);
+                self.body_acc.clear();
+            }
+        }
+    }
+
+    /// Starts a flight of the ball from where it lies. A ball that is
+    /// already in flight carries on with the flight it is on, so it still
+    /// goes back to where it last rested and can't run out the time limit.
+    fn take_off(&mut self) {
+        if self.ball.is_moving() {
+            return;
+        }
+        self.ball.resting = false;
+        self.rest_pos = self.ball.pos;
+        self.flight_start = self.time;
+    }
+
+    /// Puts the ball back at rest where its flight started, for breaking a
+    /// rule.
+    fn send_back(&mut self, fault: Fault) {
+        self.ball = Ball::new(self.rest_pos);
+        self.fault = Some(fault);
+        self.penalties += self.rules.penalty;
+        self.trail.clear();
+    }
+
+    /// Applies `force` to the ball instantaneously, replacing its velocity.
+    /// Does nothing once the ball is sunk.
+    pub fn shoot(&mut self, force: na::Vector2<f32>) {
+        if self.sunk {
+            return;
+        }
+        // F = ma, we apply a = F / m instantaneously to give velocity
+        self.ball.vel = force / Ball::MASS;
+        self.take_off();
+        self.trail.push(TrailPoint {
+            time: self.time,
+            pos: self.ball.pos,
+            speed: self.ball.vel.magnitude(),
+        });
+    }
+
+    /// Combined gravitational acceleration of all bodies on the ball at `pos`.
+    pub fn gravity_at(&self, pos: na::Point2<f32>) -> na::Vector2<f32> {
+        match &self.tree {
+            Some(tree) => tree.acceleration_at(pos, self.barnes_hut.theta, |i| {
+                self.bodies[i].acceleration_at(pos, Ball::MASS)
+            }),
+            None => field::acceleration_at(&self.bodies, pos),
+        }
+    }
+
+    /// Linear and quadratic drag coefficients on the ball at `pos`, from the
+    /// level's drag and every atmosphere it is in.
+    pub fn drag_at(&self, pos: na::Point2<f32>) -> (f32, f32) {
+        let (linear, quadratic) = self.drag.coefficients();
+        let atmospheres: f32 = self.bodies.iter()
+            .filter_map(|body| {
+                body.atmosphere.map(|atmosphere| {
+                    atmosphere.coefficient_at(body.radius, na::distance(&body.pos, &pos))
+                })
+            })
+            .sum();
+        (linear, quadratic + atmospheres)
+    }
+
+    /// Every obstacle edge with the collider it is to the ball.
+    fn edges(&self) -> impl Iterator<Item = (Collider, &Wall)> {
+        self.obstacle_edges.iter().enumerate().flat_map(|(obstacle, edges)| {
+            edges.iter().enumerate().map(move |(edge, wall)| (Collider::Obstacle { obstacle, edge }, wall))
+        })
+    }
+
+    /// Earliest contact of the ball moving from `start` at its velocity for
+    /// the last `dt` seconds of the step, together with what it hits, its
+    /// material and its velocity.
+    fn first_impact(
+        &self,
+        start: na::Point2<f32>,
+        dt: f32,
+    ) -> Option<(Impact, Collider, Material, na::Vector2<f32>)> {
+        let body_impacts = self.bodies.iter()
+            .enumerate()
+            .filter_map(|(i, body)| {
+                // sweep in the body's frame, from where it was `dt` ago
+                let motion = (self.ball.vel - body.vel) * dt;
+                let center = body.pos - body.vel * dt;
+                collision::sweep_circle_circle(start, motion, Ball::RADIUS, center, body.radius)
+                    .map(|impact| (impact, Collider::Body(i), body.material, body.vel))
+            });
+        let wall_impacts = self.walls.iter()
+            .enumerate()
+            .filter_map(|(i, wall)| {
+                collision::sweep_circle_wall(start, self.ball.vel * dt, Ball::RADIUS, wall)
+                    .map(|impact| (impact, Collider::Wall(i), wall.material, na::Vector2::zeros()))
+            });
+        let edge_impacts = self.edges()
+            .filter_map(|(collider, wall)| {
+                collision::sweep_circle_wall(start, self.ball.vel * dt, Ball::RADIUS, wall)
+                    .map(|impact| (impact, collider, wall.material, na::Vector2::zeros()))
+            });
+        body_impacts.chain(wall_impacts).chain(edge_impacts)
+            .min_by(|(a, ..), (b, ..)| a.toi.total_cmp(&b.toi))
+    }
+
+    /// Moves the ball in a straight line to `pos` over `dt` seconds, where
+    /// it ends up with `vel`. Stops at every surface it hits on the way and
+    /// bounces off it instead, so no shot is fast enough to pass through a
+    /// body or wall between two steps.
+    ///
+    /// After [`World::MAX_IMPACTS`] hits the ball goes on up to the next
+    /// surface it would hit and stops there for the step, keeping its
+    /// velocity.
+    fn advance_ball(&mut self, dt: f32, pos: na::Point2<f32>, vel: na::Vector2<f32>) {
+        // sweep along the average velocity over the step
+        self.ball.vel = (pos - self.ball.pos) / dt;
+        let mut remaining = dt;
+        let mut impacts = 0;
+        loop {
+            let motion = self.ball.vel * remaining;
+            if impacts == Self::MAX_IMPACTS {
+                // out of bounces, go as far as possible without passing
+                // through anything
+                let toi = self.first_impact(self.ball.pos, remaining).map_or(1.0, |(impact, ..)| impact.toi);
+                self.ball.pos += motion * toi;
+                break;
+            }
+            match self.first_impact(self.ball.pos, remaining) {
+                Some((impact, collider, material, surface_vel)) => {
+                    self.ball.pos += motion * impact.toi;
+                    let vel = self.ball.vel - surface_vel;
+                    self.collisions.push(Collision {
+                        tick: self.tick,
+                        collider,
+                        pos: self.ball.pos,
+                        normal: impact.normal,
+                        speed: -vel.dot(&impact.normal),
+                    });
+                    // A ball lying on a surface gets pushed into it by
+                    // gravity every step, by up to acc * dt. Left to bounce,
+                    // that approach speed would hop it off the surface
+                    // every step and it would never come to rest, so only
+                    // what exceeds it by more than the bounce threshold
+                    // counts as a real hit.
+                    let pressed = (-self.ball.acc.dot(&impact.normal) * dt).max(0.0);
+                    let material = if -vel.dot(&impact.normal) <= pressed + collision::BOUNCE_THRESHOLD {
+                        Material { restitution: 0.0, ..material }
+                    } else {
+                        material
+                    };
+                    self.ball.vel = surface_vel + collision::bounce(vel, impact.normal, &material);
+                    remaining *= 1.0 - impact.toi;
+                    impacts += 1;
+                },
+                None if impacts == 0 => {
+                    self.ball.pos = pos;
+                    self.ball.vel = vel;
+                    break;
+                },
+                None => {
+                    self.ball.pos += motion;
+                    break;
+                },
+            }
+        }
+
+        // anything the sweep couldn't prevent, like starting inside a body
+        for (i, body) in self.bodies.iter().enumerate() {
+            if let Some(contact) = collision::collide_ball_body(&mut self.ball, body) {
+                self.collisions.push(Collision::from_contact(self.tick, Collider::Body(i), self.ball.pos, contact));
+            }
+        }
+        for (i, wall) in self.walls.iter().enumerate() {
+            if let Some(contact) = collision::collide_ball_wall(&mut self.ball, wall) {
+                self.collisions.push(Collision::from_contact(self.tick, Collider::Wall(i), self.ball.pos, contact));
+            }
+        }
+        for (obstacle, edges) in self.obstacle_edges.iter().enumerate() {
+            for (edge, wall) in edges.iter().enumerate() {
+                if let Some(contact) = collision::collide_ball_wall(&mut self.ball, wall) {
+                    let collider = Collider::Obstacle { obstacle, edge };
+                    self.collisions.push(Collision::from_contact(self.tick, collider, self.ball.pos, contact));
+                }
+            }
+        }
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    pub fn step(&mut self, dt: f32) {
+        self.tick += 1;
+        self.time += dt;
+        self.collisions.clear();
+        self.fault = None;
+        self.trail.prune(self.time);
+        self.move_bodies(dt);
+        if !self.ball.is_moving() {
+            // a body moving into the ball knocks it out of rest
+            let mut knocked = false;
+            for (i, body) in self.bodies.iter().enumerate() {
+                if body.vel == na::Vector2::zeros() {
+                    continue;
+                }
+                if let Some(contact) = collision::collide_ball_body(&mut self.ball, body) {
+                    self.collisions.push(Collision::from_contact(self.tick, Collider::Body(i), self.ball.pos, contact));
+                    knocked = true;
+                }
+            }
+            if !knocked {
+                return;
+            }
+            self.take_off();
+        }
+
+        let prev_vel = self.ball.vel;
+        self.ball.acc = self.gravity_at(self.ball.pos);
+        let (pos, vel) = self.integrator.integrator().step(
+            self.ball.pos,
+            self.ball.vel,
+            dt,
+            &|pos| self.gravity_at(pos),
+        );
+        self.advance_ball(dt, pos, vel);
+        let (linear, quadratic) = self.drag_at(self.ball.pos);
+        self.ball.vel = drag::apply(self.ball.vel, linear, quadratic, dt);
+        self.ball.settle(prev_vel, dt);
+        self.trail.push(TrailPoint {
+            time: self.time,
+            pos: self.ball.pos,
+            speed: self.ball.vel.magnitude(),
+        });
+
+        if self.hole.captures(&self.ball) {
+            self.sunk = true;
+            self.ball.pos = self.hole.pos;
+            self.ball.vel = na::Vector2::zeros();
+            self.ball.acc = na::Vector2::zeros();
+            self.ball.resting = true;
+            return;
+        }
+
+        if let Some(fault) = self.rules.fault(self.ball.pos, self.time - self.flight_start, &self.collisions) {
+            self.send_back(fault);
+        }
+    }
+
+    /// Steps the world until the ball comes to rest or drops in the hole, for
+    /// at most `max_ticks` steps. Returns the number of steps taken.
+    pub fn settle(&mut self, dt: f32, max_ticks: u64) -> u64 {
+        self.settle_with(dt, max_ticks, |_| ())
+    }
+
+    /// Like [`World::settle`], calling `on_step` with the world after every
+    /// step.
+    pub fn settle_with<F: FnMut(&World)>(&mut self, dt: f32, max_ticks: u64, mut on_step: F) -> u64 {
+        let mut ticks = 0;
+        while ticks < max_ticks && self.ball.is_moving() && !self.sunk {
+            self.step(dt);
+            on_step(self);
+            ticks += 1;
+        }
+        ticks
+    }
+
+    /// Positions the ball would go through over the next `ticks` steps of
+    /// `dt` if shot with `force` now. Stops early once it comes to rest or
+    /// drops in the hole. The world itself is left untouched.
+    pub fn predict(&self, force: na::Vector2<f32>, ticks: usize, dt: f32) -> Vec<na::Point2<f32>> {
+        let mut world = self.clone();
+        world.shoot(force);
+
+        let mut path = Vec::with_capacity(ticks);
+        for _ in 0..ticks {
+            if !world.ball.is_moving() || world.is_sunk() {
+                break;
+            }
+            world.step(dt);
+            path.push(world.ball.pos);
+        }
+        path
+    }
+}
@@ -0,0 +1,51 @@
+use nalgebra as na;
+
+use gulf::asteroids::Rng;
+use gulf::body::G;
+use gulf::AsteroidField;
+
+fn field(orbit: bool) -> AsteroidField {
+    AsteroidField {
+        count: 2000,
+        center: na::Point2::origin(),
+        inner_radius: 100.0,
+        outer_radius: 1200.0,
+        min_radius: 2.0,
+        max_radius: 6.0,
+        density: 1e13,
+        orbit,
+    }
+}
+
+#[test]
+fn asteroids_start_clear_of_each_other() {
+    let keep_clear = [(na::Point2::new(500.0, 0.0), 50.0)];
+    let asteroids = field(false).generate(&mut Rng::new(7), 0.0, &keep_clear);
+    assert!(asteroids.len() > 1900, "only placed {}", asteroids.len());
+    for (i, a) in asteroids.iter().enumerate() {
+        assert!(na::distance(&a.pos, &keep_clear[0].0) >= keep_clear[0].1 + a.radius);
+        for b in asteroids[i + 1..].iter() {
+            assert!(na::distance(&a.pos, &b.pos) >= a.radius + b.radius, "{:?} overlaps {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn orbits_account_for_the_ring_inside_them() {
+    let central_mass = 3.6e17;
+    let asteroids = field(true).generate(&mut Rng::new(7), central_mass, &[]);
+    let ring_mass: f32 = asteroids.iter().map(|asteroid| asteroid.mass).sum();
+    assert!(ring_mass > central_mass);
+
+    for asteroid in asteroids.iter() {
+        let offset = asteroid.pos.coords;
+        assert!(asteroid.vel.dot(&offset).abs() < 1e-3 * asteroid.vel.magnitude() * offset.magnitude());
+    }
+    // the outermost one circles everything else
+    let outer = asteroids.iter()
+        .max_by(|a, b| a.pos.coords.magnitude().total_cmp(&b.pos.coords.magnitude()))
+        .unwrap();
+    let enclosed = central_mass + ring_mass - outer.mass;
+    let speed = (G * enclosed / outer.pos.coords.magnitude()).sqrt();
+    assert!((outer.vel.magnitude() - speed).abs() < 1e-3 * speed, "{} vs {}", outer.vel.magnitude(), speed);
+}
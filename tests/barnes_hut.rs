@@ -0,0 +1,55 @@
+use nalgebra as na;
+
+use gulf::asteroids::Rng;
+use gulf::{field, nbody, AsteroidField, BarnesHut, BigMass, Hole, World};
+
+/// A planet in a seeded ring of 1000 asteroids.
+fn asteroid_field() -> Vec<BigMass> {
+    let field = AsteroidField {
+        count: 1000,
+        center: na::Point2::origin(),
+        inner_radius: 100.0,
+        outer_radius: 1200.0,
+        min_radius: 2.0,
+        max_radius: 6.0,
+        density: 1e13,
+        orbit: false,
+    };
+    let mut bodies = vec![BigMass::new(na::Point2::origin(), 3.6e17, 30.0)];
+    bodies.extend(field.generate(&mut Rng::new(7), 0.0, &[]));
+    bodies
+}
+
+#[test]
+fn quadtree_gravity_stays_close_to_the_direct_sum() {
+    let bodies = asteroid_field();
+    let barnes_hut = BarnesHut::default();
+    let tree = barnes_hut.tree(&bodies).unwrap();
+    let approx = nbody::accelerations(&bodies, Some(&tree), barnes_hut.theta);
+    let exact = nbody::accelerations(&bodies, None, barnes_hut.theta);
+    let mut errors: Vec<f32> = approx.iter().zip(exact.iter())
+        .map(|(approx, exact)| (approx - exact).magnitude() / exact.magnitude())
+        .collect();
+    errors.sort_by(f32::total_cmp);
+
+    // theta 0.5 stays within 2% overall and 5% for 99 bodies in 100; the
+    // worst are those whose pulls from all sides nearly cancel
+    let rms = (errors.iter().map(|e| e * e).sum::<f32>() / errors.len() as f32).sqrt();
+    let p99 = errors[errors.len() * 99 / 100];
+    let max = errors[errors.len() - 1];
+    assert!(rms < 0.02, "RMS error {}", rms);
+    assert!(p99 < 0.05, "99th percentile error {}", p99);
+    assert!(max < 0.15, "largest error {}", max);
+}
+
+#[test]
+fn changing_the_threshold_rebuilds_the_tree() {
+    let bodies = asteroid_field();
+    let mut world = World::new(na::Point2::new(-1500.0, 0.0), Hole::new(na::Point2::new(1500.0, 0.0)));
+    world.set_bodies(bodies.clone());
+    let pos = na::Point2::new(1300.0, 200.0);
+    assert_ne!(world.gravity_at(pos), field::acceleration_at(&bodies, pos));
+
+    world.set_barnes_hut(BarnesHut { threshold: usize::MAX, ..BarnesHut::default() });
+    assert_eq!(world.gravity_at(pos), field::acceleration_at(&bodies, pos));
+}
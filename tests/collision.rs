@@ -0,0 +1,101 @@
+use nalgebra as na;
+
+use gulf::collision::{sweep_circle_circle, sweep_circle_wall};
+use gulf::{Ball, Drag, Hole, Material, Wall, World};
+
+const RADIUS: f32 = 10.0;
+
+#[test]
+fn head_on_hit_stops_at_the_surface() {
+    let impact = sweep_circle_circle(
+        na::Point2::new(-100.0, 0.0),
+        na::Vector2::new(200.0, 0.0),
+        RADIUS,
+        na::Point2::origin(),
+        30.0,
+    )
+    .unwrap();
+    assert!((impact.toi - 0.3).abs() < 1e-6, "toi {}", impact.toi);
+    assert!((impact.normal - na::Vector2::new(-1.0, 0.0)).magnitude() < 1e-6, "normal {}", impact.normal);
+
+    let wall = Wall::new(na::Point2::new(0.0, -50.0), na::Point2::new(0.0, 50.0));
+    let impact = sweep_circle_wall(na::Point2::new(-100.0, 0.0), na::Vector2::new(200.0, 0.0), RADIUS, &wall)
+        .unwrap();
+    assert!((impact.toi - 0.45).abs() < 1e-6, "toi {}", impact.toi);
+    assert_eq!(impact.normal, na::Vector2::new(-1.0, 0.0));
+}
+
+#[test]
+fn passing_by_misses() {
+    let start = na::Point2::new(-100.0, 50.0);
+    let motion = na::Vector2::new(200.0, 0.0);
+    assert_eq!(sweep_circle_circle(start, motion, RADIUS, na::Point2::origin(), 30.0), None);
+
+    // past the end of the wall
+    let wall = Wall::new(na::Point2::new(0.0, -30.0), na::Point2::new(0.0, 30.0));
+    assert_eq!(sweep_circle_wall(start, motion, RADIUS, &wall), None);
+
+    // stopping short of it
+    let short = na::Vector2::new(50.0, 0.0);
+    assert_eq!(sweep_circle_wall(na::Point2::new(-100.0, 0.0), short, RADIUS, &wall), None);
+}
+
+#[test]
+fn tangent_graze_touches_side_on() {
+    // passes exactly `RADIUS + 10` from the center
+    let impact = sweep_circle_circle(
+        na::Point2::new(-100.0, 20.0),
+        na::Vector2::new(200.0, 0.0),
+        RADIUS,
+        na::Point2::origin(),
+        10.0,
+    )
+    .unwrap();
+    assert_eq!(impact.toi, 0.5);
+    assert_eq!(impact.normal, na::Vector2::new(0.0, 1.0));
+
+    // which doesn't slow the ball down
+    let vel = na::Vector2::new(400.0, 0.0);
+    assert_eq!(gulf::collision::bounce(vel, impact.normal, &Material::default()), vel);
+}
+
+#[test]
+fn fast_shots_dont_tunnel() {
+    let start = na::Point2::new(-100.0, 0.0);
+    let motion = na::Vector2::new(1e5, 0.0);
+    let impact = sweep_circle_circle(start, motion, RADIUS, na::Point2::origin(), 5.0).unwrap();
+    assert!((impact.toi * motion.x - 85.0).abs() < 1e-3, "toi {}", impact.toi);
+    let wall = Wall::new(na::Point2::new(0.0, -50.0), na::Point2::new(0.0, 50.0));
+    let impact = sweep_circle_wall(start, motion, RADIUS, &wall).unwrap();
+    assert!((impact.toi * motion.x - 90.0).abs() < 1e-3, "toi {}", impact.toi);
+
+    let mut world = World::new(start, Hole::new(na::Point2::new(500.0, 500.0)));
+    world.drag = Drag::None;
+    world.walls.push(wall);
+    world.shoot(na::Vector2::new(1e5, 0.0) * Ball::MASS);
+    for _ in 0..10 {
+        world.step(1.0 / 60.0);
+        assert!(world.ball.pos.x < 0.0, "ball got through to {}", world.ball.pos);
+    }
+}
+
+#[test]
+fn a_ball_wedged_between_walls_keeps_moving() {
+    // so narrow the ball hits more than `World::MAX_IMPACTS` walls each step
+    let bouncy = Material { restitution: 1.0, friction: 0.0 };
+    let mut world = World::new(na::Point2::origin(), Hole::new(na::Point2::new(5000.0, 5000.0)));
+    world.drag = Drag::None;
+    for &y in &[-12.0, 12.0] {
+        let mut wall = Wall::new(na::Point2::new(-100.0, y), na::Point2::new(10000.0, y));
+        wall.material = bouncy;
+        world.walls.push(wall);
+    }
+    world.shoot(na::Vector2::new(600.0, 3000.0) * Ball::MASS);
+    for _ in 0..60 {
+        world.step(1.0 / 60.0);
+    }
+    assert!(world.ball.pos.x > 300.0, "ball only got to {}", world.ball.pos);
+    let speed = na::Vector2::new(600.0f32, 3000.0).magnitude();
+    assert!((world.ball.vel.magnitude() - speed).abs() < 1e-2, "speed {}", world.ball.vel.magnitude());
+    assert!(world.ball.pos.y.abs() <= 2.0 + 1e-3, "ball got out to {}", world.ball.pos);
+}
@@ -0,0 +1,116 @@
+use nalgebra as na;
+
+use gulf::{BigMass, Editor, Level, Motion, Selection};
+
+fn level() -> Level {
+    gulf::parse_level(
+        r#"{
+            "ball_start": [100.0, 300.0],
+            "bodies": [],
+            "hole": { "pos": [700.0, 300.0] },
+            "par": 3
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn placing_and_dragging_undo_and_redo_a_step_each() {
+    let mut editor = Editor::new(level());
+    assert!(!editor.can_undo());
+    editor.place_body(na::Point2::new(400.0, 200.0));
+    assert_eq!(editor.selection(), Some(Selection::Body(0)));
+
+    // grabbed off center, and moved in several steps
+    assert!(editor.begin_drag(na::Point2::new(403.0, 200.0)));
+    for x in &[420.0, 450.0, 483.0] {
+        editor.drag_to(na::Point2::new(*x, 250.0));
+    }
+    editor.end_drag();
+    assert!(!editor.is_dragging());
+    assert_eq!(editor.level().bodies[0].pos, na::Point2::new(480.0, 250.0));
+
+    assert!(editor.undo());
+    assert_eq!(editor.level().bodies[0].pos, na::Point2::new(400.0, 200.0));
+    assert!(editor.undo());
+    assert!(editor.level().bodies.is_empty());
+    assert!(!editor.undo());
+
+    assert!(editor.redo());
+    assert_eq!(editor.level().bodies[0].pos, na::Point2::new(400.0, 200.0));
+    assert!(editor.redo());
+    assert_eq!(editor.level().bodies[0].pos, na::Point2::new(480.0, 250.0));
+    assert!(!editor.redo());
+}
+
+#[test]
+fn clicking_without_moving_leaves_nothing_to_undo() {
+    let mut editor = Editor::new(level());
+    assert!(editor.begin_drag(na::Point2::new(700.0, 305.0)));
+    assert_eq!(editor.selection(), Some(Selection::Hole));
+    editor.end_drag();
+    assert!(!editor.can_undo());
+
+    // nor does clicking on nothing
+    assert!(!editor.begin_drag(na::Point2::new(400.0, 0.0)));
+    assert_eq!(editor.selection(), None);
+}
+
+#[test]
+fn editing_after_undo_drops_the_redo_history() {
+    let mut editor = Editor::new(level());
+    editor.move_hole(na::Point2::new(600.0, 100.0));
+    editor.undo();
+    assert!(editor.can_redo());
+    assert!(editor.begin_drag(na::Point2::new(100.0, 300.0)));
+    editor.drag_to(na::Point2::new(150.0, 300.0));
+    editor.end_drag();
+    assert!(!editor.can_redo());
+    assert_eq!(editor.level().ball_start, na::Point2::new(150.0, 300.0));
+    assert_eq!(editor.level().hole.pos, na::Point2::new(700.0, 300.0));
+}
+
+#[test]
+fn deleting_a_body_keeps_orbits_pointing_at_the_right_parents() {
+    let orbit = |parent| Motion::Orbit { parent, eccentricity: 0.0, period: Some(10.0), phase: 0.0 };
+    let mut level = level();
+    level.bodies = vec![
+        BigMass::new(na::Point2::new(300.0, 300.0), 3.6e17, 30.0),
+        BigMass::new(na::Point2::new(350.0, 300.0), 1e15, 5.0),
+        BigMass::new(na::Point2::new(500.0, 300.0), 3.6e17, 30.0),
+        BigMass::new(na::Point2::new(550.0, 300.0), 1e15, 5.0),
+    ];
+    level.bodies[1].motion = orbit(0);
+    level.bodies[3].motion = orbit(2);
+
+    let mut editor = Editor::new(level);
+    assert_eq!(editor.select_at(na::Point2::new(300.0, 310.0)), Some(Selection::Body(0)));
+    assert!(editor.delete_selected());
+    assert_eq!(editor.selection(), None);
+    let bodies = &editor.level().bodies;
+    assert_eq!(bodies.len(), 3);
+    assert_eq!(bodies[0].motion, Motion::Static);
+    assert_eq!(bodies[2].motion, orbit(1));
+
+    // only bodies can go
+    editor.select_at(na::Point2::new(100.0, 300.0));
+    assert!(!editor.delete_selected());
+}
+
+#[test]
+fn undoing_a_placement_forgets_the_body_selected() {
+    let mut editor = Editor::new(level());
+    editor.place_body(na::Point2::new(400.0, 200.0));
+    assert!(editor.begin_drag(na::Point2::new(400.0, 200.0)));
+    assert!(editor.undo());
+    assert_eq!(editor.selection(), None);
+    assert!(!editor.is_dragging());
+    assert_eq!(editor.selected_body(), None);
+
+    // a selection that still exists is kept
+    editor.redo();
+    editor.select_at(na::Point2::new(700.0, 300.0));
+    editor.resize_selected(5.0);
+    editor.undo();
+    assert_eq!(editor.selection(), Some(Selection::Hole));
+}
@@ -0,0 +1,69 @@
+use nalgebra as na;
+
+use gulf::{Ball, BigMass, Method};
+
+/// Largest relative change in energy per unit mass while following a
+/// circular orbit of `radius` around one body for `orbits` revolutions.
+fn energy_drift(method: Method, radius: f32, orbits: f32, dt: f32) -> f32 {
+    let body = BigMass::new(na::Point2::origin(), 3.6e17, 30.0);
+    let energy = |pos: na::Point2<f32>, vel: na::Vector2<f32>| {
+        0.5 * vel.magnitude_squared() + body.potential_at(pos)
+    };
+
+    // v = sqrt(GM / r) keeps the ball on a circle
+    let speed = (gulf::body::G * body.mass / radius).sqrt();
+    let period = 2.0 * std::f32::consts::PI * radius / speed;
+    let mut pos = na::Point2::new(radius, 0.0);
+    let mut vel = na::Vector2::new(0.0, speed);
+    let start = energy(pos, vel);
+
+    let integrator = method.integrator();
+    let acc = |pos| body.acceleration_at(pos, Ball::MASS);
+    let mut drift: f32 = 0.0;
+    for _ in 0..(orbits * period / dt) as usize {
+        let (new_pos, new_vel) = integrator.step(pos, vel, dt, &acc);
+        pos = new_pos;
+        vel = new_vel;
+        drift = drift.max(((energy(pos, vel) - start) / start).abs());
+    }
+    drift
+}
+
+const RADIUS: f32 = 200.0;
+const DT: f32 = 1.0 / 60.0;
+
+#[test]
+fn explicit_euler_keeps_gaining_energy() {
+    let short = energy_drift(Method::Euler, RADIUS, 2.0, DT);
+    let long = energy_drift(Method::Euler, RADIUS, 20.0, DT);
+    assert!(short > 0.1, "drift {}", short);
+    assert!(long > short, "drift {} after 20 orbits, {} after 2", long, short);
+}
+
+#[test]
+fn semi_implicit_euler_energy_stays_bounded() {
+    let short = energy_drift(Method::SemiImplicitEuler, RADIUS, 2.0, DT);
+    let long = energy_drift(Method::SemiImplicitEuler, RADIUS, 20.0, DT);
+    assert!(long < 0.01, "drift {}", long);
+    assert!(long < short * 1.1, "drift {} after 20 orbits, {} after 2", long, short);
+}
+
+#[test]
+fn verlet_and_rk4_barely_drift() {
+    for &method in &[Method::VelocityVerlet, Method::Rk4] {
+        let drift = energy_drift(method, RADIUS, 20.0, DT);
+        assert!(drift < 1e-4, "{}: drift {}", method, drift);
+    }
+}
+
+#[test]
+fn higher_order_integrators_drift_less() {
+    let drift = |method| energy_drift(method, RADIUS, 10.0, DT);
+    let euler = drift(Method::Euler);
+    let semi_implicit = drift(Method::SemiImplicitEuler);
+    assert!(semi_implicit < euler, "semi-implicit drift {} vs euler {}", semi_implicit, euler);
+    for &method in &[Method::VelocityVerlet, Method::Rk4] {
+        let higher = drift(method);
+        assert!(higher < semi_implicit, "{} drift {} vs semi-implicit {}", method, higher, semi_implicit);
+    }
+}
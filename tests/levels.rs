@@ -0,0 +1,70 @@
+use std::fs;
+use std::path::Path;
+
+use gulf::verify;
+use gulf::{Drag, Hole, Motion, SolverConfig};
+
+#[test]
+fn shipped_levels_can_be_finished_within_par() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("levels");
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no levels in {}", dir.display());
+
+    // a coarser search than the default, which is plenty for the shipped
+    // levels and keeps this quick in debug builds
+    let config = SolverConfig {
+        angle_steps: 36,
+        power_steps: 8,
+        refine_iterations: 16,
+        ..SolverConfig::default()
+    };
+
+    for path in paths {
+        let level = gulf::load_level(&path).unwrap();
+        let report = verify::verify_level(&level, &config);
+        assert!(report.is_ok(), "{}: {:?}", path.display(), report);
+    }
+}
+
+#[test]
+fn saved_levels_load_back_the_same() {
+    let mut level = gulf::load_level(Path::new(env!("CARGO_MANIFEST_DIR")).join("levels/03-binary.json")).unwrap();
+    level.seed = 42;
+    level.n_body = true;
+    level.bodies[1].motion = Motion::Orbit { parent: 0, eccentricity: 0.2, period: None, phase: 0.5 };
+    level.hole.radius = 20.0;
+
+    let path = std::env::temp_dir().join(format!("gulf-level-{}.json", std::process::id()));
+    gulf::save_level(&path, &level).unwrap();
+    let loaded = gulf::load_level(&path);
+    fs::remove_file(&path).unwrap();
+    let loaded = loaded.unwrap();
+    assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&level).unwrap());
+    assert_eq!(gulf::level_hash(&loaded), gulf::level_hash(&level));
+}
+
+#[test]
+fn left_out_fields_load_as_their_defaults() {
+    let level = gulf::parse_level(
+        r#"{
+            "ball_start": [100.0, 300.0],
+            "bodies": [{ "pos": [300.0, 300.0], "mass": 3.6e17, "radius": 30.0 }],
+            "hole": { "pos": [500.0, 300.0] },
+            "par": 2
+        }"#,
+    )
+    .unwrap();
+    assert!(level.walls.is_empty());
+    assert_eq!(level.seed, 0);
+    assert_eq!(level.hole.radius, Hole::DEFAULT_RADIUS);
+    assert_eq!(level.hole.max_speed, Hole::DEFAULT_MAX_SPEED);
+    assert_eq!(level.drag, Drag::default());
+    assert_eq!(level.bodies[0].motion, Motion::Static);
+    assert!(level.obstacles.is_empty() && level.asteroids.is_empty());
+    assert!(!level.n_body);
+}
@@ -0,0 +1,91 @@
+use std::f32::consts::PI;
+
+use nalgebra as na;
+
+use gulf::motion::positions_at;
+use gulf::{BigMass, Motion};
+
+/// A planet at the origin with a moon starting on its periapsis at
+/// `(100, 0)`, on an orbit of `eccentricity` taking `period` seconds.
+fn moon(eccentricity: f32, period: Option<f32>) -> Vec<BigMass> {
+    let mut moon = BigMass::new(na::Point2::new(100.0, 0.0), 1e14, 5.0);
+    moon.motion = Motion::Orbit { parent: 0, eccentricity, period, phase: 0.0 };
+    vec![BigMass::new(na::Point2::origin(), 3.6e17, 30.0), moon]
+}
+
+fn assert_near(found: na::Point2<f32>, expected: na::Point2<f32>, tolerance: f32) {
+    assert!(na::distance(&found, &expected) <= tolerance, "at {} instead of {}", found, expected);
+}
+
+#[test]
+fn orbits_reach_apoapsis_at_half_period_and_return_after_one() {
+    for &eccentricity in &[0.0, 0.5, 0.95] {
+        let bodies = moon(eccentricity, Some(10.0));
+        let apoapsis = 100.0 * (1.0 + eccentricity) / (1.0 - eccentricity);
+        let tolerance = 1e-3 * apoapsis;
+        assert_near(positions_at(&bodies, 0.0)[1], na::Point2::new(100.0, 0.0), tolerance);
+        assert_near(positions_at(&bodies, 5.0)[1], na::Point2::new(-apoapsis, 0.0), tolerance);
+        assert_near(positions_at(&bodies, 10.0)[1], na::Point2::new(100.0, 0.0), tolerance);
+        assert_near(positions_at(&bodies, 30.0)[1], na::Point2::new(100.0, 0.0), tolerance);
+    }
+}
+
+#[test]
+fn orbits_solve_keplers_equation() {
+    for &eccentricity in &[0.3, 0.95] {
+        let bodies = moon(eccentricity, Some(10.0));
+        let semi_major = 100.0 / (1.0 - eccentricity);
+        let semi_minor = semi_major * (1.0 - eccentricity * eccentricity).sqrt();
+        for &time in &[1.0, 2.5, 4.0, 7.5] {
+            // back from where the moon is to its eccentric anomaly E, which
+            // should give M = E - e sin E for the time
+            let pos = positions_at(&bodies, time)[1];
+            let anomaly = (pos.y / semi_minor).atan2(pos.x / semi_major + eccentricity).rem_euclid(2.0 * PI);
+            let mean_anomaly = anomaly - eccentricity * anomaly.sin();
+            assert!(
+                (mean_anomaly - 2.0 * PI * time / 10.0).abs() < 1e-3,
+                "e = {}: mean anomaly {} at {} s",
+                eccentricity, mean_anomaly, time
+            );
+        }
+    }
+}
+
+#[test]
+fn orbit_period_follows_keplers_third_law() {
+    let bodies = moon(0.5, None);
+    let semi_major: f32 = 200.0;
+    let period = 2.0 * PI * (semi_major.powi(3) / (gulf::body::G * (bodies[0].mass + bodies[1].mass))).sqrt();
+    assert_near(positions_at(&bodies, period / 2.0)[1], na::Point2::new(-300.0, 0.0), 0.3);
+    assert_near(positions_at(&bodies, period)[1], na::Point2::new(100.0, 0.0), 0.1);
+}
+
+#[test]
+fn ping_pong_turns_around_at_the_far_end() {
+    let mut body = BigMass::new(na::Point2::new(10.0, 20.0), 1e15, 10.0);
+    body.motion = Motion::PingPong { offset: na::Vector2::new(100.0, 0.0), period: 4.0, phase: 0.0 };
+    let bodies = [body];
+    assert_near(positions_at(&bodies, 1.0)[0], na::Point2::new(60.0, 20.0), 1e-3);
+    assert_near(positions_at(&bodies, 2.0)[0], na::Point2::new(110.0, 20.0), 1e-3);
+    assert_near(positions_at(&bodies, 3.0)[0], na::Point2::new(60.0, 20.0), 1e-3);
+    assert_near(positions_at(&bodies, 4.0)[0], na::Point2::new(10.0, 20.0), 1e-3);
+
+    // a quarter of the way through from the start
+    let mut body = bodies[0].clone();
+    body.motion = Motion::PingPong { offset: na::Vector2::new(100.0, 0.0), period: 4.0, phase: 0.25 };
+    assert_near(positions_at(&[body], 0.0)[0], na::Point2::new(60.0, 20.0), 1e-3);
+}
+
+#[test]
+fn circles_keep_their_distance_from_the_center() {
+    let mut body = BigMass::new(na::Point2::new(10.0, 20.0), 1e15, 10.0);
+    body.motion = Motion::Circle { center: na::Vector2::new(50.0, 0.0), period: 8.0, phase: 0.0 };
+    let bodies = [body];
+    let center = na::Point2::new(60.0, 20.0);
+    for step in 0..16 {
+        let pos = positions_at(&bodies, step as f32 * 0.5)[0];
+        assert!((na::distance(&pos, &center) - 50.0).abs() < 1e-3, "{} off the circle", pos);
+    }
+    assert_near(positions_at(&bodies, 4.0)[0], na::Point2::new(110.0, 20.0), 1e-3);
+    assert_near(positions_at(&bodies, 8.0)[0], na::Point2::new(10.0, 20.0), 1e-3);
+}
@@ -0,0 +1,72 @@
+use nalgebra as na;
+
+use gulf::body::G;
+use gulf::{nbody, BigMass, Hole, World};
+
+fn moving(pos: na::Point2<f32>, vel: na::Vector2<f32>, mass: f32, radius: f32) -> BigMass {
+    let mut body = BigMass::new(pos, mass, radius);
+    body.vel = vel;
+    body
+}
+
+fn momentum(bodies: &[BigMass]) -> na::Vector2<f32> {
+    bodies.iter().map(|body| body.vel * body.mass).sum()
+}
+
+#[test]
+fn merging_conserves_mass_momentum_and_area() {
+    let mut bodies = vec![
+        moving(na::Point2::new(0.0, 0.0), na::Vector2::new(10.0, 0.0), 3e16, 20.0),
+        moving(na::Point2::new(25.0, 0.0), na::Vector2::new(-40.0, 5.0), 1e16, 10.0),
+        moving(na::Point2::new(30.0, 8.0), na::Vector2::new(0.0, -20.0), 2e15, 5.0),
+        moving(na::Point2::new(500.0, 0.0), na::Vector2::zeros(), 1e16, 10.0),
+    ];
+    let mass: f32 = bodies.iter().map(|body| body.mass).sum();
+    let area: f32 = bodies.iter().map(|body| body.radius.powi(2)).sum();
+    let before = momentum(&bodies);
+
+    let mut placed = bodies.clone();
+    assert_eq!(nbody::merge_overlapping(&mut bodies, &mut placed), 2);
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(placed.len(), 2);
+    assert!((bodies.iter().map(|body| body.mass).sum::<f32>() - mass).abs() <= mass * 1e-6);
+    assert!((bodies.iter().map(|body| body.radius.powi(2)).sum::<f32>() - area).abs() <= area * 1e-6);
+    let after = momentum(&bodies);
+    assert!((after - before).magnitude() <= before.magnitude() * 1e-5, "{} became {}", before, after);
+    assert_eq!(bodies[1].pos, na::Point2::new(500.0, 0.0));
+}
+
+#[test]
+fn merging_survives_a_body_gone_nan() {
+    let mut bodies = vec![
+        BigMass::new(na::Point2::new(f32::NAN, 0.0), 1e16, 10.0),
+        BigMass::new(na::Point2::new(0.0, 0.0), 1e16, 10.0),
+        BigMass::new(na::Point2::new(5.0, 0.0), 1e16, 10.0),
+    ];
+    let mut placed = bodies.clone();
+    assert_eq!(nbody::merge_overlapping(&mut bodies, &mut placed), 1);
+}
+
+#[test]
+fn velocity_verlet_keeps_a_binary_bound() {
+    // two equal bodies circling their common center 200 apart
+    let (mass, separation) = (1e16, 200.0);
+    let speed = (G * mass * separation / 2.0).sqrt() / separation;
+    let period = std::f32::consts::PI * separation / speed;
+    let mut world = World::new(na::Point2::new(0.0, 2000.0), Hole::new(na::Point2::new(0.0, 2500.0)));
+    world.n_body = true;
+    world.set_bodies(vec![
+        moving(na::Point2::new(-100.0, 0.0), na::Vector2::new(0.0, -speed), mass, 10.0),
+        moving(na::Point2::new(100.0, 0.0), na::Vector2::new(0.0, speed), mass, 10.0),
+    ]);
+
+    let dt = 1.0 / 60.0;
+    for _ in 0..(3.0 * period / dt) as usize {
+        world.step(dt);
+        let bodies = world.bodies();
+        let distance = na::distance(&bodies[0].pos, &bodies[1].pos);
+        assert!((distance - separation).abs() < separation * 0.01, "drifted to {} apart", distance);
+        let center = na::center(&bodies[0].pos, &bodies[1].pos);
+        assert!(center.coords.magnitude() < 0.1, "center moved to {}", center);
+    }
+}
@@ -0,0 +1,44 @@
+use nalgebra as na;
+
+use gulf::collision::Collider;
+use gulf::{Ball, LevelError};
+
+fn level(obstacles: &str) -> Result<gulf::Level, LevelError> {
+    gulf::parse_level(&format!(
+        r#"{{
+            "ball_start": [0.0, 0.0],
+            "bodies": [],
+            "hole": {{ "pos": [0.0, 500.0] }},
+            "par": 2,
+            "drag": {{ "type": "none" }},
+            "walls": [{{ "a": [-50.0, -100.0], "b": [50.0, -100.0] }}],
+            "obstacles": {}
+        }}"#,
+        obstacles
+    ))
+}
+
+#[test]
+fn hitting_an_obstacle_names_the_obstacle_and_edge() {
+    let level = level(r#"[
+        { "type": "rectangle", "center": [-200.0, 0.0], "size": [20.0, 20.0] },
+        { "type": "rectangle", "center": [200.0, 0.0], "size": [20.0, 200.0] }
+    ]"#)
+    .unwrap();
+    let mut world = level.world();
+    world.shoot(na::Vector2::new(300.0, 0.0) * Ball::MASS);
+    let mut hit = None;
+    while hit.is_none() && world.ball.is_moving() {
+        world.step(1.0 / 60.0);
+        hit = world.collisions.first().map(|collision| collision.collider);
+    }
+    // the left side of the second rectangle, which is its last edge
+    assert_eq!(hit, Some(Collider::Obstacle { obstacle: 1, edge: 3 }));
+}
+
+#[test]
+fn polygons_need_three_points() {
+    let err = level(r#"[{ "type": "polygon", "points": [[100.0, 0.0], [200.0, 0.0]] }]"#).unwrap_err();
+    assert!(matches!(err, LevelError::OpenObstacle { obstacle: 0 }), "{}", err);
+    assert!(level(r#"[{ "type": "polygon", "points": [[100.0, 0.0], [200.0, 0.0], [150.0, 50.0]] }]"#).is_ok());
+}
@@ -0,0 +1,77 @@
+use gulf::{Level, Replay, ReplayError, Shot};
+
+const TICK_RATE: u32 = 60;
+
+/// A level with a planet on a path and two free bodies pulling on each
+/// other, so nothing but the seed and the shots decides how it plays.
+fn level() -> Level {
+    gulf::parse_level(
+        r#"{
+            "ball_start": [100.0, 100.0],
+            "bodies": [
+                { "pos": [400.0, 300.0], "mass": 3.6e17, "radius": 30.0 },
+                { "pos": [550.0, 300.0], "mass": 1e16, "radius": 10.0,
+                  "motion": { "type": "orbit", "parent": 0, "eccentricity": 0.3 } },
+                { "pos": [250.0, 450.0], "mass": 5e16, "radius": 15.0 }
+            ],
+            "hole": { "pos": [700.0, 500.0] },
+            "par": 3,
+            "n_body": true
+        }"#,
+    )
+    .unwrap()
+}
+
+/// Plays `shots` on `level`, each once the ball has come to rest or after
+/// two seconds at most, and records them.
+fn record(level: &Level, shots: &[Shot]) -> Replay {
+    let mut world = level.world();
+    let mut replay = Replay::new(level, TICK_RATE);
+    for &shot in shots {
+        replay.record(world.tick(), shot);
+        world.shoot(shot.force());
+        for _ in 0..2 * TICK_RATE {
+            world.step(replay.dt());
+            if !world.ball.is_moving() {
+                break;
+            }
+        }
+    }
+    replay.finish(&world);
+    replay
+}
+
+#[test]
+fn replays_survive_saving_and_play_back_the_same() {
+    let level = level();
+    let shots = [Shot::from_angle(0.6, 400.0), Shot::from_angle(2.0, 250.0), Shot::from_angle(-0.3, 600.0)];
+    let replay = record(&level, &shots);
+    assert_ne!(replay.final_pos, level.ball_start);
+
+    let json = serde_json::to_string(&replay).unwrap();
+    let loaded: Replay = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, replay);
+    let world = loaded.verify(&level).unwrap();
+    assert_eq!(world.tick(), replay.final_tick);
+    assert_eq!(world.ball.pos, replay.final_pos);
+}
+
+#[test]
+fn replays_catch_a_different_level_or_ending() {
+    let level = level();
+    let replay = record(&level, &[Shot::from_angle(0.6, 400.0)]);
+
+    let mut other = level.clone();
+    other.par += 1;
+    match replay.verify(&other) {
+        Err(ReplayError::LevelMismatch { .. }) => (),
+        result => panic!("expected a level mismatch, got {:?}", result),
+    }
+
+    let mut tampered = replay.clone();
+    tampered.shots[0].shot.power *= 1.01;
+    match tampered.verify(&level) {
+        Err(ReplayError::Diverged { expected, .. }) => assert_eq!(expected, replay.final_pos),
+        result => panic!("expected the replay to diverge, got {:?}", result),
+    }
+}
@@ -0,0 +1,88 @@
+use nalgebra as na;
+
+use gulf::{Ball, Fault, Level, World};
+
+const DT: f32 = 1.0 / 60.0;
+
+/// A level with the ball at the origin, a light body to the right of it and
+/// nothing slowing the ball down, playing by `rules`.
+fn level(rules: &str) -> Level {
+    gulf::parse_level(&format!(
+        r#"{{
+            "ball_start": [0.0, 0.0],
+            "bodies": [{{ "pos": [300.0, 0.0], "mass": 1e10, "radius": 30.0 }}],
+            "hole": {{ "pos": [0.0, 500.0] }},
+            "par": 2,
+            "drag": {{ "type": "none" }},
+            "rules": {}
+        }}"#,
+        rules
+    ))
+    .unwrap()
+}
+
+/// Shoots the ball from the start of `level` at `vel` and steps until it is
+/// sent back or gives up, returning the world and the fault, if any.
+fn shoot(level: &Level, vel: na::Vector2<f32>) -> (World, Option<Fault>) {
+    let mut world = level.world();
+    world.shoot(vel * Ball::MASS);
+    for _ in 0..600 {
+        world.step(DT);
+        if world.fault.is_some() {
+            break;
+        }
+    }
+    let fault = world.fault;
+    (world, fault)
+}
+
+#[test]
+fn leaving_the_bounds_sends_the_ball_back_with_a_penalty() {
+    let level = level(r#"{ "bounds": { "min": [-100.0, -100.0], "max": [100.0, 100.0] }, "penalty": 2 }"#);
+    let (world, fault) = shoot(&level, na::Vector2::new(0.0, -300.0));
+    assert_eq!(fault, Some(Fault::OutOfBounds));
+    assert_eq!(world.ball.pos, level.ball_start);
+    assert!(!world.ball.is_moving());
+    assert_eq!(world.penalties(), 2);
+}
+
+#[test]
+fn flying_past_the_time_limit_sends_the_ball_back_with_a_penalty() {
+    let level = level(r#"{ "time_limit": 1.0 }"#);
+    let (world, fault) = shoot(&level, na::Vector2::new(0.0, -50.0));
+    assert_eq!(fault, Some(Fault::TimeLimit));
+    assert!((world.time() - 1.0).abs() <= 2.0 * DT, "sent back after {} s", world.time());
+    assert_eq!(world.ball.pos, level.ball_start);
+    assert!(!world.ball.is_moving());
+    assert_eq!(world.penalties(), 1);
+}
+
+#[test]
+fn hitting_a_body_at_crash_speed_crashes_into_it() {
+    let level = level(r#"{ "crash_speed": 200.0 }"#);
+    let (world, fault) = shoot(&level, na::Vector2::new(300.0, 0.0));
+    assert_eq!(fault, Some(Fault::Crash { body: 0 }));
+    assert_eq!(world.ball.pos, level.ball_start);
+    assert_eq!(world.penalties(), 1);
+
+    let (world, fault) = shoot(&level, na::Vector2::new(100.0, 0.0));
+    assert_eq!(fault, None);
+    assert_eq!(world.penalties(), 0);
+}
+
+#[test]
+fn shooting_again_mid_flight_keeps_the_flight_going() {
+    let level = level(r#"{ "time_limit": 1.0 }"#);
+    let mut world = level.world();
+    world.shoot(na::Vector2::new(0.0, -50.0) * Ball::MASS);
+    for _ in 0..30 {
+        world.step(DT);
+    }
+    world.shoot(na::Vector2::new(0.0, -50.0) * Ball::MASS);
+    while world.fault.is_none() && world.time() < 3.0 {
+        world.step(DT);
+    }
+    assert_eq!(world.fault, Some(Fault::TimeLimit));
+    assert!(world.time() < 1.1, "sent back after {} s", world.time());
+    assert_eq!(world.ball.pos, level.ball_start);
+}
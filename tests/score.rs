@@ -0,0 +1,43 @@
+use gulf::{HoleScore, ScoreLabel, Scorecard};
+
+fn label(par: u32, strokes: u32) -> ScoreLabel {
+    HoleScore { par, strokes }.label()
+}
+
+#[test]
+fn scores_are_named_by_strokes_against_par() {
+    assert_eq!(HoleScore { par: 4, strokes: 6 }.to_par(), 2);
+    assert_eq!(HoleScore { par: 5, strokes: 3 }.to_par(), -2);
+
+    assert_eq!(label(3, 1), ScoreLabel::HoleInOne);
+    // a hole in one beats naming how far under par it is
+    assert_eq!(label(5, 1), ScoreLabel::HoleInOne);
+    assert_eq!(label(6, 3), ScoreLabel::Albatross);
+    assert_eq!(label(5, 3), ScoreLabel::Eagle);
+    assert_eq!(label(4, 3), ScoreLabel::Birdie);
+    assert_eq!(label(4, 4), ScoreLabel::Par);
+    assert_eq!(label(4, 5), ScoreLabel::Bogey);
+    assert_eq!(label(4, 6), ScoreLabel::DoubleBogey);
+    assert_eq!(label(4, 7), ScoreLabel::TripleBogey);
+    assert_eq!(label(9, 5), ScoreLabel::Under(4));
+    assert_eq!(label(2, 7), ScoreLabel::Over(5));
+
+    assert_eq!(label(4, 6).to_string(), "Double bogey");
+    assert_eq!(label(9, 5).to_string(), "4 under par");
+    assert_eq!(label(2, 7).to_string(), "5 over par");
+}
+
+#[test]
+fn scorecards_total_up_and_survive_saving() {
+    let mut card = Scorecard::new();
+    assert_eq!(card.record(3, 2), HoleScore { par: 3, strokes: 2 });
+    card.record(4, 6);
+    card.record(5, 5);
+    assert_eq!(card.total_par(), 12);
+    assert_eq!(card.total_strokes(), 13);
+    assert_eq!(card.to_par(), 1);
+
+    let json = serde_json::to_string(&card).unwrap();
+    assert_eq!(serde_json::from_str::<Scorecard>(&json).unwrap(), card);
+    assert_eq!(Scorecard::new().to_par(), 0);
+}
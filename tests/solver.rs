@@ -0,0 +1,52 @@
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use gulf::{Level, SolverConfig};
+
+fn level(name: &str) -> Level {
+    gulf::load_level(Path::new(env!("CARGO_MANIFEST_DIR")).join("levels").join(name)).unwrap()
+}
+
+/// A coarser search than the default, quick enough for debug builds.
+fn config() -> SolverConfig {
+    SolverConfig {
+        angle_steps: 36,
+        power_steps: 8,
+        refine_iterations: 16,
+        ..SolverConfig::default()
+    }
+}
+
+#[test]
+fn finds_a_one_stroke_solution_on_the_straight_level() {
+    let solution = gulf::solve(&level("01-straight.json"), &config());
+    assert!(solution.holed, "{:?}", solution);
+    assert_eq!(solution.strokes(), 1);
+    assert_eq!(solution.penalties, 0);
+}
+
+#[test]
+fn found_shots_sink_the_ball_when_played() {
+    let level = level("02-planet.json");
+    let config = config();
+    let solution = gulf::solve(&level, &config);
+    assert!(solution.holed, "{:?}", solution);
+
+    let mut world = level.world();
+    for shot in solution.shots.iter() {
+        world.shoot(shot.force());
+        world.settle(1.0 / config.tick_rate as f32, config.max_ticks);
+    }
+    assert!(world.is_sunk());
+}
+
+#[test]
+fn cancelled_searches_stop_early() {
+    let cancel = Arc::new(AtomicBool::new(true));
+    let config = SolverConfig { cancel: Some(cancel), ..SolverConfig::default() };
+    let start = Instant::now();
+    assert!(gulf::solver::best_shot(&level("02-planet.json").world(), &config).is_none());
+    assert!(start.elapsed() < Duration::from_secs(1), "took {:?}", start.elapsed());
+}